@@ -13,12 +13,20 @@ CLA: int = 0x00
 P1: int = 0x00
 P2: int = 0x00
 
+SIGN_FLAG_RETURN_DIGEST: int = 0x01
+SIGN_FLAG_SERIALIZED_SIGNATURE: int = 0x02
+
 class InsType(IntEnum):
     GET_VERSION    = 0x00
     GET_APP_NAME   = 0x00
     VERIFY_ADDRESS = 0x01
     GET_PUBLIC_KEY = 0x02
     SIGN_TX        = 0x03
+    SIGN_TX_SPONSOR = 0x04
+    SIGN_MESSAGE   = 0x05
+    PROVIDE_DESCRIPTOR = 0x06
+    PROVIDE_CERTIFICATE = 0x07
+    PROVIDE_MULTISIG = 0x08
 
 class Errors(IntEnum):
     SW_DENY                    = 0x6985
@@ -35,6 +43,14 @@ class Errors(IntEnum):
     SW_TX_HASH_FAIL            = 0xB006
     SW_BAD_STATE               = 0xB007
     SW_SIGNATURE_FAIL          = 0xB008
+    SW_SENDER_MISMATCH         = 0xB009
+    SW_WRONG_INTENT_SCOPE      = 0xB00A
+    SW_WRONG_INTENT_VERSION    = 0xB00B
+    SW_WRONG_APP_ID            = 0xB00C
+    SW_WRONG_DESCRIPTOR_SIGNATURE = 0xB00D
+    SW_DESCRIPTOR_MISMATCH     = 0xB00E
+    SW_WRONG_CERTIFICATE       = 0xB00F
+    SW_NOT_MULTISIG_MEMBER     = 0xB010
 
 
 def split_message(message: bytes, max_size: int) -> List[bytes]:
@@ -83,15 +99,68 @@ class Client:
         return pub_key_len, pub_key, chain_code_len, chain_code
 
 
-    def sign_tx(self, path: str, transaction: bytes) -> bytes:
+    # flags: the flags byte to send as is, instead of the one built from return_digest and serialized
+    def sign_tx(self, path: str, transaction: bytes, return_digest: bool = False, serialized: bool = False, flags: Optional[int] = None) -> bytes:
+        return self.sign_tx_impl(InsType.SIGN_TX, path, transaction, return_digest, serialized, flags)
+
+    def sign_tx_as_sponsor(self, path: str, transaction: bytes) -> bytes:
+        return self.sign_tx_impl(InsType.SIGN_TX_SPONSOR, path, transaction)
+
+    def sign_tx_impl(self, ins, path: str, transaction: bytes, return_digest: bool = False, serialized: bool = False, flags: Optional[int] = None) -> bytes:
         tx_len = (len(transaction)).to_bytes(4, byteorder='little')
         payload = [tx_len + transaction, pack_derivation_path(path)]
+        if flags is None:
+            flags = (SIGN_FLAG_RETURN_DIGEST if return_digest else 0) | (SIGN_FLAG_SERIALIZED_SIGNATURE if serialized else 0)
+        if flags:
+            payload.append(bytes([flags]))
         return self.send_fn(cla=CLA,
-                     ins=InsType.SIGN_TX,
+                     ins=ins,
                      p1=P1,
                      p2=P2,
                      payload=payload)
 
+    # flags: the flags byte to send as is, instead of the one built from serialized
+    def sign_personal_message(self, path: str, message: bytes, serialized: bool = False, flags: Optional[int] = None) -> bytes:
+        msg_len = (len(message)).to_bytes(4, byteorder='little')
+        payload = [msg_len + message, pack_derivation_path(path)]
+        if flags is None:
+            flags = SIGN_FLAG_SERIALIZED_SIGNATURE if serialized else 0
+        if flags:
+            payload.append(bytes([flags]))
+        return self.send_fn(cla=CLA,
+                     ins=InsType.SIGN_MESSAGE,
+                     p1=P1,
+                     p2=P2,
+                     payload=payload)
+
+    def provide_descriptor(self, descriptor: bytes, signature: bytes) -> bytes:
+        descriptor_len = (len(descriptor)).to_bytes(4, byteorder='little')
+        payload = [descriptor_len + descriptor, bytes([len(signature)]) + signature]
+        return self.send_fn(cla=CLA,
+                     ins=InsType.PROVIDE_DESCRIPTOR,
+                     p1=P1,
+                     p2=P2,
+                     payload=payload)
+
+    def provide_certificate(self, certificate: bytes) -> bytes:
+        certificate_len = (len(certificate)).to_bytes(4, byteorder='little')
+        return self.send_fn(cla=CLA,
+                     ins=InsType.PROVIDE_CERTIFICATE,
+                     p1=P1,
+                     p2=P2,
+                     payload=[certificate_len + certificate])
+
+    # members: (flag, public key, weight) of each key of the multisig
+    def provide_multisig(self, threshold: int, members: List[Tuple[int, bytes, int]]) -> bytes:
+        multisig = threshold.to_bytes(2, byteorder='little') + bytes([len(members)])
+        for flag, public_key, weight in members:
+            multisig += bytes([flag]) + public_key + bytes([weight])
+        return self.send_fn(cla=CLA,
+                     ins=InsType.PROVIDE_MULTISIG,
+                     p1=P1,
+                     p2=P2,
+                     payload=[multisig])
+
     def get_async_response(self) -> Optional[RAPDU]:
         return self.backend.last_async_response
 
@@ -1,5 +1,7 @@
 import pytest
 
+from hashlib import blake2b
+
 from application_client.client import Client, Errors
 from contextlib import contextmanager
 from ragger.bip import calculate_public_key_and_chaincode, CurveChoice
@@ -18,10 +20,37 @@ def test_get_public_key_no_confirm(backend):
         assert address.hex() == "56b19e720f3bfa8caaef806afdd5dfaffd0d6ec9476323a14d1638ad734b2ba5"
 
 
-# In this test we check that the GET_PUBLIC_KEY works in confirmation mode
+# Addresses of secp256k1 keys are derived from their compressed public key, with the flag 0x01
+def test_get_public_key_secp256k1(backend):
+    client = Client(backend, use_block_protocol=True)
+    _, public_key, _, address = client.get_public_key(path="m/54'/784'/0'/0/0")
+
+    assert len(public_key) == 33 and public_key[0] in (2, 3)
+    assert address == blake2b(bytes([0x01]) + public_key, digest_size=32).digest()
+
+
+# Addresses of secp256r1 keys are derived from their compressed public key, with the flag 0x02
+def test_get_public_key_secp256r1(backend):
+    client = Client(backend, use_block_protocol=True)
+    _, public_key, _, address = client.get_public_key(path="m/74'/784'/0'/0/0")
+
+    assert len(public_key) == 33 and public_key[0] in (2, 3)
+    assert address == blake2b(bytes([0x02]) + public_key, digest_size=32).digest()
+
+
+# Paths of other purposes or coin types are rejected, and ECDSA paths must have Sui's shape
+def test_get_public_key_unknown_scheme_rejected(backend):
+    client = Client(backend, use_block_protocol=True)
+    for path in ["m/45'/784'/0'", "m/54'/60'/0'/0/0", "m/54'/784'/0'/0'/0'", "m/74'/784'/0'"]:
+        with pytest.raises(ExceptionRAPDU):
+            client.get_public_key(path=path)
+
+
+# In this test we check that the GET_PUBLIC_KEY works in confirmation mode, for a path of Sui's
+# standard form which is shown without warning
 def test_get_public_key_confirm_accepted(backend, scenario_navigator, firmware, navigator):
     client = Client(backend, use_block_protocol=True)
-    path = "m/44'/784'/0'"
+    path = "m/44'/784'/0'/0'/0'"
 
     def nav_task():
         scenario_navigator.address_review_approve()
@@ -31,7 +60,7 @@ def test_get_public_key_confirm_accepted(backend, scenario_navigator, firmware,
 
     def check_result(result):
         _, public_key, _, address = result
-        assert public_key.hex() == "6fc6f39448ad7af0953b78b16d0f840e6fe718ba4a89384239ff20ed088da2fa"
-        assert address.hex() == "56b19e720f3bfa8caaef806afdd5dfaffd0d6ec9476323a14d1638ad734b2ba5"
+        assert public_key.hex() == "6eea79cdaaa4e01eec6449f0c0efcc128bb43bfcd56f9eaeed81075122c3665a"
+        assert address.hex() == "6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21"
 
     run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
@@ -3,44 +3,837 @@ import concurrent.futures
 import time
 import base64
 
+from ecdsa import NIST256p, SigningKey, SECP256k1
+from ecdsa.util import sigencode_der
+from hashlib import blake2b, sha256
+
 from application_client.client import Client, Errors
 from contextlib import contextmanager
 from ragger.error import ExceptionRAPDU
 from ragger.navigator import NavIns, NavInsID
-from utils import ROOT_SCREENSHOT_PATH, check_signature_validity, run_apdu_and_nav_tasks_concurrently
+from utils import ROOT_SCREENSHOT_PATH, check_signature_validity, check_ecdsa_signature_validity, review_showing, review_with_unusual_path, run_apdu_and_nav_tasks_concurrently
+
+# can sign a simple Sui transfer transaction
+def test_sign_tx_sui_transfer(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+    assert len(public_key) == 32
+
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction, return_digest=True)
+
+    def nav_task():
+        if firmware.device.startswith("nano"):
+            navigator.navigate_and_compare(
+                instructions=[ NavInsID.RIGHT_CLICK # Transfer SUI
+                               , NavInsID.RIGHT_CLICK, NavInsID.RIGHT_CLICK # From ...
+                               , NavInsID.RIGHT_CLICK, NavInsID.RIGHT_CLICK # To ...
+                               , NavInsID.RIGHT_CLICK # Amount
+                               , NavInsID.RIGHT_CLICK # Account
+                               , NavInsID.RIGHT_CLICK # Max Gas
+                               , NavInsID.RIGHT_CLICK # Digest
+                               , NavInsID.RIGHT_CLICK # Sign Transaction?
+                               , NavInsID.BOTH_CLICK
+                              ]
+                , timeout=10
+                , test_case_name="test_sign_tx_sui_transfer"
+                , path=scenario_navigator.screenshot_path
+                , screen_change_before_first_instruction=True
+                , screen_change_after_last_instruction=False
+            )
+        else:
+            scenario_navigator.review_approve()
+
+    def check_result(result):
+        assert len(result) == 96
+        assert check_signature_validity(public_key, result[:64], transaction)
+        assert result[64:] == blake2b(b"TransactionData::" + transaction[3:], digest_size=32).digest()
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# can return the signature serialized as Sui RPCs accept it, with the digest
+def test_sign_tx_serialized_signature(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction, return_digest=True, serialized=True)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^To$", "^0x4f2370b2", "^Amount$", "^SUI 0.001"])
+
+    def check_result(result):
+        # ed25519 flag, signature, public key, then the digest
+        assert len(result) == 1 + 64 + 32 + 32
+        assert result[0] == 0x00
+        assert check_signature_validity(public_key, result[1:65], transaction)
+        assert result[65:97] == public_key
+        assert result[97:] == blake2b(b"TransactionData::" + transaction[3:], digest_size=32).digest()
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# shows when a transaction expires
+def test_sign_tx_with_expiration(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # The transfer above, valid until the end of epoch 512
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000002000100020000000000000000204c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c2a000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        if firmware.device.startswith("nano"):
+            navigator.navigate([ NavInsID.RIGHT_CLICK # Transfer SUI
+                                 , NavInsID.RIGHT_CLICK, NavInsID.RIGHT_CLICK # From ...
+                                 , NavInsID.RIGHT_CLICK, NavInsID.RIGHT_CLICK # To ...
+                                 , NavInsID.RIGHT_CLICK # Amount
+                                 , NavInsID.RIGHT_CLICK # Account
+                                 , NavInsID.RIGHT_CLICK # Max Gas
+                                 , NavInsID.RIGHT_CLICK # Expiration
+                                 , NavInsID.RIGHT_CLICK # Digest
+                                 , NavInsID.RIGHT_CLICK # Sign Transaction?
+                                 , NavInsID.BOTH_CLICK
+                                ]
+                               , timeout=10
+                               , screen_change_before_first_instruction=True
+                               , screen_change_after_last_instruction=False)
+        else:
+            scenario_navigator.review_approve(do_comparison=False)
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# can sign a Move call receiving an object sent to another object
+def test_sign_tx_move_call_receiving(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # 0xabc::wallet::receive_coin(owned object 0x1111..., receiving object 0x2222...)
+    transaction = bytes.fromhex('00000000000201001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01022222222222222222222222222222222222222222222222222222222222222222090000000000000020dddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd01000000000000000000000000000000000000000000000000000000000000000abc0677616c6c65740c726563656976655f636f696e00020100000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^Received [Oo]bj", "^0x2222"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# clear signs 0x2::transfer::public_transfer as a transfer of objects
+def test_sign_tx_public_transfer(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # 0x2::transfer::public_transfer<Coin<SUI>>(owned object 0x1111..., 0xa7a7...)
+    transaction = bytes.fromhex('00000000000201001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee0020a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a701000000000000000000000000000000000000000000000000000000000000000002087472616e736665720f7075626c69635f7472616e736665720107000000000000000000000000000000000000000000000000000000000000000204636f696e04436f696e01070000000000000000000000000000000000000000000000000000000000000002037375690353554900020100000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^To$", "^0xa7a7", "^Object 1$", "^0x1111"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# clear signs 0x2::pay::join_vec as a merge of coins
+def test_sign_tx_join_vec(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # 0x2::pay::join_vec<SUI>(0x1111..., MakeMoveVec([0x1212..., 0x1313...]))
+    transaction = bytes.fromhex('00000000000301001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01001212121212121212121212121212121212121212121212121212121212121212040000000000000020edededededededededededededededededededededededededededededededed01001313131313131313131313131313131313131313131313131313131313131313050000000000000020ecececececececececececececececececececececececececececececececec02050107000000000000000000000000000000000000000000000000000000000000000204636f696e04436f696e010700000000000000000000000000000000000000000000000000000000000000020373756903535549000201010001020000000000000000000000000000000000000000000000000000000000000000000203706179086a6f696e5f76656301070000000000000000000000000000000000000000000000000000000000000002037375690353554900020100000200006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^Merge Coins$", "^2 coins into", "^(2 coins into )?0x1111"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# Trusted by builds with the descriptor_test_key feature
+DESCRIPTOR_TEST_KEY = SigningKey.from_string(sha256(b"sui app descriptor test key").digest(), curve=SECP256k1)
+
+# Descriptor signatures are over this prefix, then the descriptor
+DESCRIPTOR_SIGNING_DOMAIN = b"sui-descriptor"
+
+def sign_descriptor(key: SigningKey, descriptor: bytes) -> bytes:
+    return key.sign_deterministic(DESCRIPTOR_SIGNING_DOMAIN + descriptor, hashfunc=sha256, sigencode=sigencode_der)
+
+def sized(b: bytes) -> bytes:
+    return bytes([len(b)]) + b
+
+# describes 0xabc::vault::deposit<T>(vault: &mut Vault<T>, amount: u64, beneficiary: address), the
+# amount being of the coin T, the first type argument
+def vault_deposit_descriptor(amount_format: bytes = b'\x02\x00') -> bytes:
+    return (b'\x01' + bytes.fromhex('0000000000000000000000000000000000000000000000000000000000000abc')
+            + sized(b'vault') + sized(b'deposit') + sized(b'Deposit to vault')
+            + b'\x03'
+            + sized(b'Vault') + b'\x00'
+            + sized(b'Amount') + amount_format
+            + sized(b'Beneficiary') + b'\x01')
+
+def provide_descriptor_or_skip(client: Client, descriptor: bytes):
+    signature = sign_descriptor(DESCRIPTOR_TEST_KEY, descriptor)
+    try:
+        client.provide_descriptor(descriptor, signature)
+    except ExceptionRAPDU as e:
+        if e.status == Errors.SW_WRONG_DESCRIPTOR_SIGNATURE:
+            pytest.skip("the app is not built with the descriptor_test_key feature")
+        raise
+
+# 0xabc::vault::deposit<0x2::sui::SUI>(owned object 0x1111..., 1500000000, 0x3333...)
+VAULT_DEPOSIT_TX = bytes.fromhex('00000000000301001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee0008002f6859000000000020333333333333333333333333333333333333333333333333333333333333333301000000000000000000000000000000000000000000000000000000000000000abc057661756c74076465706f73697401070000000000000000000000000000000000000000000000000000000000000002037375690353554900030100000101000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+# clear signs a Move call of a third-party package with a signed descriptor
+def test_sign_tx_described_move_call(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    provide_descriptor_or_skip(client, vault_deposit_descriptor())
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=VAULT_DEPOSIT_TX)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^Amount$", "^SUI 1.5", "^Beneficiary$", "^0x3333"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, VAULT_DEPOSIT_TX)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# rejects a descriptor which is not signed by a trusted key, or whose signature is not over the
+# descriptor signing domain
+@pytest.mark.parametrize("sign", [
+    lambda d: sign_descriptor(SigningKey.from_string(sha256(b"not a trusted key").digest(), curve=SECP256k1), d),
+    lambda d: DESCRIPTOR_TEST_KEY.sign_deterministic(d, hashfunc=sha256, sigencode=sigencode_der),
+])
+def test_provide_descriptor_wrong_signature_rejected(backend, firmware, sign):
+    client = Client(backend, use_block_protocol=True)
+    descriptor = vault_deposit_descriptor()
+    signature = sign(descriptor)
+
+    with pytest.raises(ExceptionRAPDU) as e:
+        client.provide_descriptor(descriptor, signature)
+
+    assert e.value.status == Errors.SW_WRONG_DESCRIPTOR_SIGNATURE
+
+# rejects a descriptor whose size is not exactly that of the descriptor, as not all that is signed
+# would be parsed, or not all that is parsed would be signed
+@pytest.mark.parametrize("resize", [lambda d: d + b'\x00', lambda d: d[:-1]])
+def test_provide_descriptor_wrong_size_rejected(backend, firmware, resize):
+    client = Client(backend, use_block_protocol=True)
+    descriptor = resize(vault_deposit_descriptor())
+    signature = sign_descriptor(DESCRIPTOR_TEST_KEY, descriptor)
+
+    with pytest.raises(ExceptionRAPDU) as e:
+        client.provide_descriptor(descriptor, signature)
+
+    # Rejected before its signature is checked
+    assert e.value.status != Errors.SW_WRONG_DESCRIPTOR_SIGNATURE
+
+# rejects a certificate which is not signed by the Ledger PKI
+def test_provide_certificate_wrong_rejected(backend, firmware):
+    client = Client(backend, use_block_protocol=True)
+
+    with pytest.raises(ExceptionRAPDU) as e:
+        client.provide_certificate(bytes([0x01] * 64))
+
+    assert e.value.status == Errors.SW_WRONG_CERTIFICATE
+
+# shows an amount of a coin the app doesn't know in raw units, the coin being the type argument
+def test_sign_tx_described_unknown_coin_amount(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    provide_descriptor_or_skip(client, vault_deposit_descriptor())
+
+    # 0xabc::vault::deposit<0xabc::token::TOKEN>(owned object 0x1111..., 1500000000, 0x3333...)
+    transaction = bytes.fromhex('00000000000301001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee0008002f6859000000000020333333333333333333333333333333333333333333333333333333333333333301000000000000000000000000000000000000000000000000000000000000000abc057661756c74076465706f73697401070000000000000000000000000000000000000000000000000000000000000abc05746f6b656e05544f4b454e00030100000101000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^Amount$", "^1500000000 "])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# rejects a Move call whose arguments don't match the descriptor
+@pytest.mark.parametrize("amount_format", [
+    # The amount as an object
+    b'\x00',
+    # The amount of the coin of a second type argument, which the call doesn't have
+    b'\x02\x01',
+])
+def test_sign_tx_descriptor_mismatch_rejected(backend, firmware, amount_format):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    provide_descriptor_or_skip(client, vault_deposit_descriptor(amount_format=amount_format))
+
+    with pytest.raises(ExceptionRAPDU) as e:
+        client.sign_tx(path=path, transaction=VAULT_DEPOSIT_TX)
+
+    assert e.value.status == Errors.SW_DESCRIPTOR_MISMATCH
+
+# A 2-of-3 multisig of the key of m/44'/784'/0'/0'/0' and two other ed25519 keys
+def multisig_members(public_key: bytes):
+    return [(0x00, public_key, 1), (0x00, bytes([0x11] * 32), 1), (0x00, bytes([0x22] * 32), 1)]
+
+def multisig_address(threshold: int, members) -> bytes:
+    h = blake2b(digest_size=32)
+    h.update(bytes([0x03]) + threshold.to_bytes(2, byteorder='little'))
+    for flag, public_key, weight in members:
+        h.update(bytes([flag]) + public_key + bytes([weight]))
+    return h.digest()
+
+# can sign a transaction sent from a multisig, as one of its members
+def test_sign_tx_multisig_member(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, address = client.get_public_key(path=path)
+    members = multisig_members(public_key)
+    multisig = multisig_address(2, members)
+
+    assert client.provide_multisig(2, members) == multisig
+
+    # The SUI transfer above, sent from the multisig, which also pays for gas
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000').replace(address, multisig)
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^From \\(multisig", "^0x" + multisig.hex()[:8]])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# refuses to sign for a multisig which the key is not a member of
+def test_sign_tx_not_multisig_member_rejected(backend, firmware):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, _, _, address = client.get_public_key(path=path)
+    members = multisig_members(bytes([0x33] * 32))
+    client.provide_multisig(2, members)
+
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000').replace(address, multisig_address(2, members))
+
+    with pytest.raises(ExceptionRAPDU) as e:
+        client.sign_tx(path=path, transaction=transaction)
+
+    assert e.value.status == Errors.SW_NOT_MULTISIG_MEMBER
+
+# discards a provided multisig when signing a personal message, as it only applies to the next transaction
+def test_sign_personal_message_discards_multisig(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+    # A multisig the key is not a member of, which signing the transaction would be rejected for
+    client.provide_multisig(2, multisig_members(bytes([0x33] * 32)))
+
+    message = b"Hello, Sui"
+
+    def message_apdu_task():
+        return client.sign_personal_message(path=path, message=message)
+
+    def message_nav_task():
+        review_showing(firmware, navigator, ["^Message$", "^Hello, Sui"])
+
+    def check_message_result(result):
+        assert len(result) == 64
+
+    run_apdu_and_nav_tasks_concurrently(message_apdu_task, message_nav_task, check_message_result)
+
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^From$", "^0x6fb21fee"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# refuses to blind sign while a multisig is provided, as it can't check the multisig is the sender
+def test_sign_tx_blind_sign_multisig_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+    client.provide_multisig(2, multisig_members(public_key))
+
+    # The unknown transaction blind signed below
+    transaction = bytes.fromhex('00000000000100206fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210204010da11ceb0b060000000300000000020000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000000000000201010200000100006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    with blind_sign_enabled(firmware, navigator):
+        with pytest.raises(ExceptionRAPDU) as e:
+            client.sign_tx(path=path, transaction=transaction)
+
+    assert len(e.value.data) == 0
+
+# describes 0xabc::types::check(value), with the Pure format of the type of value
+def types_check_descriptor(kind: int, optional: bool) -> bytes:
+    return (b'\x01' + bytes.fromhex('0000000000000000000000000000000000000000000000000000000000000abc')
+            + sized(b'types') + sized(b'check') + sized(b'Check a value')
+            + b'\x01'
+            + sized(b'Value') + b'\x03' + bytes([kind, 1 if optional else 0]))
+
+# 0xabc::types::check(the pure input value)
+def types_check_tx(value: bytes) -> bytes:
+    return (bytes.fromhex('00000000000100') + bytes([len(value)]) + value
+            + bytes.fromhex('01000000000000000000000000000000000000000000000000000000000000000abc05747970657305636865636b00010100006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000'))
+
+# decodes pure values of every type a descriptor can declare
+@pytest.mark.parametrize("kind,optional,value,text", [
+    (0, False, b'\x01', "^true$"),
+    (1, False, b'\xff', "^255$"),
+    (2, False, (513).to_bytes(2, byteorder='little'), "^513$"),
+    (3, False, (70000).to_bytes(4, byteorder='little'), "^70000$"),
+    (4, False, (2**64 - 1).to_bytes(8, byteorder='little'), "^184467440737"),
+    (5, False, (2**100).to_bytes(16, byteorder='little'), "^126765060022"),
+    (6, False, (2**200).to_bytes(32, byteorder='little'), "^160693804425"),
+    (7, False, bytes([0xa7] * 32), "^0xa7a7a7a7a7"),
+    (8, False, sized(bytes([0x01, 0x02, 0xff])), "^0x0102ff$"),
+    (9, False, sized(b'Hello, Sui'), "^Hello, Sui$"),
+    (4, True, b'\x01' + (42).to_bytes(8, byteorder='little'), "^42$"),
+    (4, True, b'\x00', "^None$"),
+])
+def test_sign_tx_described_pure_value(backend, scenario_navigator, firmware, navigator, kind, optional, value, text):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    provide_descriptor_or_skip(client, types_check_descriptor(kind, optional))
+    transaction = types_check_tx(value)
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^Value$", text])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# rejects a pure value which doesn't decode as the type the descriptor declares
+def test_sign_tx_described_pure_value_mismatch_rejected(backend, firmware):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # A u64 in 4 bytes
+    provide_descriptor_or_skip(client, types_check_descriptor(4, False))
+
+    with pytest.raises(ExceptionRAPDU) as e:
+        client.sign_tx(path=path, transaction=types_check_tx((70000).to_bytes(4, byteorder='little')))
+
+    assert e.value.status == Errors.SW_DESCRIPTOR_MISMATCH
+
+# rejects a MakeMoveVec whose optional element type has a tag other than 0 or 1
+def test_sign_tx_make_move_vec_invalid_type_tag_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # The join_vec above, with 2 as the tag of the type of the elements
+    transaction = bytes.fromhex('00000000000301001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01001212121212121212121212121212121212121212121212121212121212121212040000000000000020edededededededededededededededededededededededededededededededed01001313131313131313131313131313131313131313131313131313131313131313050000000000000020ecececececececececececececececececececececececececececececececec02050207000000000000000000000000000000000000000000000000000000000000000204636f696e04436f696e010700000000000000000000000000000000000000000000000000000000000000020373756903535549000201010001020000000000000000000000000000000000000000000000000000000000000000000203706179086a6f696e5f76656301070000000000000000000000000000000000000000000000000000000000000002037375690353554900020100000200006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    sign_tx_not_recognized(client, firmware, navigator, path, transaction)
+
+# should reject an expiration whose chain identifier isn't a 32 byte digest
+def test_sign_tx_with_malformed_expiration_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # The transfer above, with a chain identifier length prefix of 33
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000002000100020000000000000000214c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c4c2a000000')
+
+    sign_tx_not_recognized(client, firmware, navigator, path, transaction)
+
+# shows the target and the arguments of a Move call it doesn't interpret, pure ones as their bytes
+def test_sign_tx_move_call(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # 0xabc::vault::withdraw(owned object 0x1111..., pure input 250000000)
+    transaction = bytes.fromhex('00000000000201001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee000880b2e60e0000000001000000000000000000000000000000000000000000000000000000000000000abc057661756c7408776974686472617700020100000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^Target$", "^0x0000", "^Argument 1$", r"^Input 0 \(object\)", "^Argument 2$", "^0x80b2e60e00000000$"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# should reject a Move call alongside other commands, which could use its results in a way the review doesn't show
+def test_sign_tx_move_call_with_other_commands_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # The call above, then TransferObjects([Result(0)], 0xa7a7...)
+    transaction = bytes.fromhex('00000000000301001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee000880b2e60e000000000020a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a702000000000000000000000000000000000000000000000000000000000000000abc057661756c74087769746864726177000201000001010001010200000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    sign_tx_not_recognized(client, firmware, navigator, path, transaction)
+
+# shows the object which coins are merged into
+def test_sign_tx_merge_coins(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # MergeCoins(0x1111..., [0x1212..., 0x1313...])
+    transaction = bytes.fromhex('00000000000301001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01001212121212121212121212121212121212121212121212121212121212121212040000000000000020edededededededededededededededededededededededededededededededed01001313131313131313131313131313131313131313131313131313131313131313050000000000000020ecececececececececececececececececececececececececececececececec0103010000020101000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^Merge Coins$", "^2 coins into", "^(2 coins into )?0x1111"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# shows when coins are merged into the gas coin
+def test_sign_tx_merge_coins_into_gas_coin(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # MergeCoins(GasCoin, [0x1212...])
+    transaction = bytes.fromhex('00000000000101001212121212121212121212121212121212121212121212121212121212121212040000000000000020edededededededededededededededededededededededededededededededed010300010100006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^Merge Coins$", "^1 coin into Gas"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# should reject merging an object which is not an owned coin input, like a shared object
+def test_sign_tx_merge_shared_object_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # MergeCoins(0x1111..., [shared object 0x1414...])
+    transaction = bytes.fromhex('00000000000201001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee010114141414141414141414141414141414141414141414141414141414141414140200000000000000010103010000010101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    sign_tx_not_recognized(client, firmware, navigator, path, transaction)
+
+# shows the ID of every transferred object
+def test_sign_tx_transfer_objects(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # TransferObjects([0x1111..., 0x1212...], 0xa7a7...)
+    transaction = bytes.fromhex('00000000000301001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01001212121212121212121212121212121212121212121212121212121212121212040000000000000020edededededededededededededededededededededededededededededededed0020a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a70101020100000101000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^To$", "^0xa7a7", "^Object 1$", "^0x1111", "^Object 2$", "^0x1212"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# should reject transferring more objects than a review can show
+def test_sign_tx_too_many_objects_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # TransferObjects of the 9 objects 0x1111... through 0x1919... to 0xa7a7...
+    transaction = bytes.fromhex('00000000000a01001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01001212121212121212121212121212121212121212121212121212121212121212030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01001313131313131313131313131313131313131313131313131313131313131313030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01001414141414141414141414141414141414141414141414141414141414141414030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01001515151515151515151515151515151515151515151515151515151515151515030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01001616161616161616161616161616161616161616161616161616161616161616030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01001717171717171717171717171717171717171717171717171717171717171717030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01001818181818181818181818181818181818181818181818181818181818181818030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01001919191919191919191919191919191919191919191919191919191919191919030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee0020a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a70101090100000101000102000103000104000105000106000107000108000109006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    sign_tx_not_recognized(client, firmware, navigator, path, transaction)
+
+# shows that transferring the gas coin sends the entire SUI balance
+def test_sign_tx_entire_balance(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # TransferObjects([GasCoin], 0xa7a7...)
+    transaction = bytes.fromhex('0000000000010020a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7010101000100006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^To$", "^0xa7a7", "^Amount$", "^Entire SUI"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# should reject transferring the gas coin along with a coin split from it
+def test_sign_tx_entire_balance_with_split_coin_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # SplitCoins(GasCoin, [Input(0)]) followed by TransferObjects([GasCoin, Result(0)], Input(1))
+    transaction = bytes.fromhex('000000000002000840420f00000000000020a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7020200010100000102000200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    sign_tx_not_recognized(client, firmware, navigator, path, transaction)
+
+# shows every recipient and amount of a batch of payouts, and their total
+def test_sign_tx_payouts(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # SplitCoins(GasCoin, [1 SUI, 2.5 SUI]), then the first coin to 0xa7a7... and the second to 0xa8a8...
+    transaction = bytes.fromhex('000000000004000800ca9a3b00000000000800f90295000000000020a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a70020a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a8a80302000201000001010001010300000000010200010103000001000103006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, [r"^To \(1/2\)$", "^0xa7a7", r"^Amount \(1/2\)$", r"^SUI 1\.0$",
+                                             r"^To \(2/2\)$", "^0xa8a8", r"^Amount \(2/2\)$", r"^SUI 2\.5$",
+                                             "^Total Amount$", r"^SUI 3\.5$"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# should reject a split coin which is not transferred, as it would stay with the sender without being shown
+def test_sign_tx_leftover_split_coin_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # SplitCoins(GasCoin, [1 SUI, 2.5 SUI]), then only the first coin to 0xa7a7...
+    transaction = bytes.fromhex('000000000003000800ca9a3b00000000000800f90295000000000020a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a702020002010000010100010103000000000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    sign_tx_not_recognized(client, firmware, navigator, path, transaction)
+
+# shows amounts of coins in the registry in their units
+def test_sign_tx_known_coin_transfer(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
 
-# can sign a simple Sui transfer transaction
-def test_sign_tx_sui_transfer(backend, scenario_navigator, firmware, navigator):
+    # 0x2::pay::split_and_transfer<USDC>(0x1111..., 12500000, 0xa7a7...)
+    transaction = bytes.fromhex('00000000000301001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee000820bcbe00000000000020a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a701000000000000000000000000000000000000000000000000000000000000000002037061791273706c69745f616e645f7472616e736665720107dba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e70475736463045553444300030100000101000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^0xa7a7", "^Amount$", r"^USDC 12\.5$"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# shows the type of coins not in the registry, and their amount in raw units
+def test_sign_tx_unknown_coin_transfer(backend, scenario_navigator, firmware, navigator):
     client = Client(backend, use_block_protocol=True)
-    path = "m/44'/784'/0'"
+    path = "m/44'/784'/0'/0'/0'"
 
     _, public_key, _, _ = client.get_public_key(path=path)
-    assert len(public_key) == 32
 
-    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000')
+    # 0x2::pay::split_and_transfer<0xabc::token::TOKEN>(0x1111..., 1234, 0xa7a7...)
+    transaction = bytes.fromhex('00000000000301001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee0008d2040000000000000020a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a701000000000000000000000000000000000000000000000000000000000000000002037061791273706c69745f616e645f7472616e7366657201070000000000000000000000000000000000000000000000000000000000000abc05746f6b656e05544f4b454e00030100000101000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
 
     def apdu_task():
         return client.sign_tx(path=path, transaction=transaction)
 
     def nav_task():
-        if firmware.device.startswith("nano"):
-            navigator.navigate_and_compare(
-                instructions=[ NavInsID.RIGHT_CLICK # Transfer SUI
-                               , NavInsID.RIGHT_CLICK, NavInsID.RIGHT_CLICK # From ...
-                               , NavInsID.RIGHT_CLICK, NavInsID.RIGHT_CLICK # To ...
-                               , NavInsID.RIGHT_CLICK # Amount
-                               , NavInsID.RIGHT_CLICK # Max Gas
-                               , NavInsID.RIGHT_CLICK # Sign Transaction?
-                               , NavInsID.BOTH_CLICK
-                              ]
-                , timeout=10
-                , test_case_name="test_sign_tx_sui_transfer"
-                , path=scenario_navigator.screenshot_path
-                , screen_change_before_first_instruction=True
-                , screen_change_after_last_instruction=False
-            )
-        else:
-            scenario_navigator.review_approve()
+        review_showing(firmware, navigator, ["^Coin Type$", "^0xabc::token", "^0xa7a7", "^Amount$", r"^1234 \(raw"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# should reject mixing another coin with SUI split from the gas coin, as amounts would be added up across types
+def test_sign_tx_coin_mixed_with_sui_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # The USDC transfer above, then SplitCoins(GasCoin, [1000000]) and TransferObjects([Result(1)], 0xa7a7...)
+    transaction = bytes.fromhex('00000000000401001111111111111111111111111111111111111111111111111111111111111111030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee000820bcbe00000000000020a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7000840420f000000000003000000000000000000000000000000000000000000000000000000000000000002037061791273706c69745f616e645f7472616e736665720107dba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e704757364630455534443000301000001010001020002000101030001010201000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    sign_tx_not_recognized(client, firmware, navigator, path, transaction)
+
+# shows the validator and the amount of a stake
+def test_sign_tx_stake(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # SplitCoins(GasCoin, [1 SUI]), then 0x3::sui_system::request_add_stake(0x5, Result(0), 0xb1b1...)
+    transaction = bytes.fromhex('000000000003000800ca9a3b00000000010100000000000000000000000000000000000000000000000000000000000000050100000000000000010020b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1020200010100000000000000000000000000000000000000000000000000000000000000000000030a7375695f73797374656d11726571756573745f6164645f7374616b6500030101000200000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^Validator$", "^0xb1b1", "^Amount$", r"^SUI 1\.0$"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# should reject a stake through another shared object than the Sui system state
+def test_sign_tx_stake_wrong_system_state_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # The stake above, with the shared object 0x1414... instead of 0x5
+    transaction = bytes.fromhex('000000000003000800ca9a3b00000000010114141414141414141414141414141414141414141414141414141414141414140200000000000000010020b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1020200010100000000000000000000000000000000000000000000000000000000000000000000030a7375695f73797374656d11726571756573745f6164645f7374616b6500030101000200000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    sign_tx_not_recognized(client, firmware, navigator, path, transaction)
+
+# shows the staked SUI object which is withdrawn
+def test_sign_tx_unstake(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # 0x3::sui_system::request_withdraw_stake(0x5, 0x1515...)
+    transaction = bytes.fromhex('0000000000020101000000000000000000000000000000000000000000000000000000000000000501000000000000000101001515151515151515151515151515151515151515151515151515151515151515030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee010000000000000000000000000000000000000000000000000000000000000000030a7375695f73797374656d16726571756573745f77697468647261775f7374616b6500020100000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^Staked SUI$", "^0x1515"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# should reject withdrawing stake alongside a transfer, which the unstake review doesn't show
+def test_sign_tx_unstake_with_transfer_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # The unstake above, then SplitCoins(GasCoin, [1000000]) and TransferObjects([Result(1)], 0xa7a7...)
+    transaction = bytes.fromhex('0000000000040101000000000000000000000000000000000000000000000000000000000000000501000000000000000101001515151515151515151515151515151515151515151515151515151515151515030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee000840420f00000000000020a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7a7030000000000000000000000000000000000000000000000000000000000000000030a7375695f73797374656d16726571756573745f77697468647261775f7374616b65000201000001010002000101020001010201000103006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    sign_tx_not_recognized(client, firmware, navigator, path, transaction)
+
+# shows the pure arguments of calls to framework functions as values of the type of their parameter,
+# when the call matches the function's type parameters and parameters
+@pytest.mark.parametrize("transaction,texts", [
+    # 0x2::coin::update_name<0xabc::token::TOKEN>(0x1616..., 0x1919..., "My Token")
+    ('00000000000301001616161616161616161616161616161616161616161616161616161616161616030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee01001919191919191919191919191919191919191919191919191919191919191919030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee0009084d7920546f6b656e0100000000000000000000000000000000000000000000000000000000000000000204636f696e0b7570646174655f6e616d6501070000000000000000000000000000000000000000000000000000000000000abc05746f6b656e05544f4b454e00030100000101000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000',
+     ["^Argument 3$", "^My Token$"]),
+    # 0x2::kiosk::withdraw(shared object 0x1717..., 0x1818..., Some(5000000000))
+    ('0000000000030101171717171717171717171717171717171717171717171717171717171717171704000000000000000101001818181818181818181818181818181818181818181818181818181818181818030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee00090100f2052a0100000001000000000000000000000000000000000000000000000000000000000000000002056b696f736b08776974686472617700030100000101000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000',
+     ["^Argument 3$", "^5000000000$"]),
+    # 0x2::kiosk::withdraw<0x2::sui::SUI>(...), which has no type parameter, so its arguments are shown as bytes
+    ('0000000000030101171717171717171717171717171717171717171717171717171717171717171704000000000000000101001818181818181818181818181818181818181818181818181818181818181818030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee00090100f2052a0100000001000000000000000000000000000000000000000000000000000000000000000002056b696f736b08776974686472617701070000000000000000000000000000000000000000000000000000000000000002037375690353554900030100000101000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000',
+     ["^Argument 3$", "^0x0100f2052a01000000$"]),
+])
+def test_sign_tx_known_signature_move_call(backend, scenario_navigator, firmware, navigator, transaction, texts):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+    transaction = bytes.fromhex(transaction)
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, texts)
 
     def check_result(result):
         assert len(result) == 64
@@ -48,14 +841,25 @@ def test_sign_tx_sui_transfer(backend, scenario_navigator, firmware, navigator):
 
     run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
 
+# should reject a call to a framework function with a pure argument which isn't of the type of its parameter
+def test_sign_tx_known_signature_wrong_type_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # 0x2::kiosk::set_allow_extensions(shared object 0x1717..., 0x1818..., 2), 2 not being a bool
+    transaction = bytes.fromhex('0000000000030101171717171717171717171717171717171717171717171717171717171717171704000000000000000101001818181818181818181818181818181818181818181818181818181818181818030000000000000020eeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeeee00010201000000000000000000000000000000000000000000000000000000000000000002056b696f736b147365745f616c6c6f775f657874656e73696f6e7300030100000101000102006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    sign_tx_not_recognized(client, firmware, navigator, path, transaction)
+
 # can blind sign an unknown transaction
 def test_sign_tx_blind_sign(backend, scenario_navigator, firmware, navigator):
     client = Client(backend, use_block_protocol=True)
-    path = "m/44'/784'/0'"
+    path = "m/44'/784'/0'/0'/0'"
 
     _, public_key, _, _ = client.get_public_key(path=path)
 
-    transaction = bytes.fromhex('00000000050205546e7f126d2f40331a543b9608439b582fd0d103000000000000002080fdabcc90498e7eb8413b140c4334871eeafa5a86203fd9cfdb032f604f49e1284af431cf032b5d85324135bf9a3073e920d7f5020000000000000020a06f410c175e828c24cee84cb3bd95cff25c33fbbdcb62c6596e8e423784ffe702d08074075c7097f361e8b443e2075a852a2292e8a08074075c7097f361e8b443e2075a852a2292e80180969800000000001643fb2578ff7191c643079a62c1cca8ec2752bc05546e7f126d2f40331a543b9608439b582fd0d103000000000000002080fdabcc90498e7eb8413b140c4334871eeafa5a86203fd9cfdb032f604f49e101000000000000002c01000000000000')
+    # Publish of a package, then a transfer of its upgrade capability
+    transaction = bytes.fromhex('00000000000100206fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210204010da11ceb0b060000000300000000020000000000000000000000000000000000000000000000000000000000000001000000000000000000000000000000000000000000000000000000000000000201010200000100006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a26fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
 
     def apdu_task():
         return client.sign_tx(path=path, transaction=transaction)
@@ -64,6 +868,14 @@ def test_sign_tx_blind_sign(backend, scenario_navigator, firmware, navigator):
         if firmware.device.startswith("nano"):
             navigator.navigate_and_compare(
                 instructions=[ NavInsID.RIGHT_CLICK # Warning...
+                               , NavInsID.RIGHT_CLICK, NavInsID.RIGHT_CLICK # From
+                               , NavInsID.RIGHT_CLICK # Max Gas
+                               , NavInsID.RIGHT_CLICK # Inputs
+                               , NavInsID.RIGHT_CLICK # Commands
+                               , NavInsID.RIGHT_CLICK # Command (1/2): Publish
+                               , NavInsID.RIGHT_CLICK # Command (2/2): TransferObjects
+                               , NavInsID.RIGHT_CLICK # Account
+                               , NavInsID.RIGHT_CLICK # Digest
                                , NavInsID.RIGHT_CLICK, NavInsID.RIGHT_CLICK # Transaction Hash
                                , NavInsID.RIGHT_CLICK # Blind Sign Transaction?
                                , NavInsID.BOTH_CLICK]
@@ -103,11 +915,58 @@ def test_sign_tx_blind_sign(backend, scenario_navigator, firmware, navigator):
     with blind_sign_enabled(firmware, navigator):
         run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
 
+# blind signing an unsupported transaction shows a summary of what could be parsed
+def test_sign_tx_blind_sign_summary(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # MakeMoveVec of a pure input, then a call to 0xdee9::clob_v2::swap
+    transaction = bytes.fromhex('000000000001000800ca9a3b000000000205000101000000000000000000000000000000000000000000000000000000000000000000dee907636c6f625f7632047377617000010200006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21e803000000000000809698000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        nano = firmware.device.startswith("nano")
+        if not nano:
+            # Dismiss the "Blind signing ahead" screen
+            navigator.navigate([NavInsID.USE_CASE_CHOICE_REJECT]
+                               , timeout=20
+                               , screen_change_before_first_instruction=True
+                               , screen_change_after_last_instruction=True)
+        review_showing(firmware, navigator
+                       , ["^From$", "^0x6fb21fee", "^Max Gas$", "^SUI 0.01"
+                          , "^Commands$", "^2$", "^MakeMoveVec$", "^MoveCall", "0xdee9::clob_v2"]
+                       , screen_change_before_first_instruction=nano)
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    with blind_sign_enabled(firmware, navigator):
+        run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# refuses to blind sign a transaction whose sender is not the address of the signing key
+def test_sign_tx_blind_sign_sender_mismatch_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # The transaction above, sent from 0x3333333333333333333333333333333333333333333333333333333333333333
+    transaction = bytes.fromhex('000000000001000800ca9a3b000000000205000101000000000000000000000000000000000000000000000000000000000000000000dee907636c6f625f7632047377617000010200003333333333333333333333333333333333333333333333333333333333333333015d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d5d070000000000000020000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f3333333333333333333333333333333333333333333333333333333333333333e803000000000000809698000000000000')
+
+    with blind_sign_enabled(firmware, navigator):
+        with pytest.raises(ExceptionRAPDU) as e:
+            client.sign_tx(path=path, transaction=transaction)
+
+    assert e.value.status == Errors.SW_SENDER_MISMATCH
+
 # Transaction signature refused test
 # The test will ask for a transaction signature that will be refused on screen
 def test_sign_tx_refused(backend, scenario_navigator, firmware, navigator):
     client = Client(backend, use_block_protocol=True)
-    path = "m/44'/784'/0'"
+    path = "m/44'/784'/0'/0'/0'"
 
     transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000')
 
@@ -121,7 +980,9 @@ def test_sign_tx_refused(backend, scenario_navigator, firmware, navigator):
                                , NavInsID.RIGHT_CLICK, NavInsID.RIGHT_CLICK # From ...
                                , NavInsID.RIGHT_CLICK, NavInsID.RIGHT_CLICK # To ...
                                , NavInsID.RIGHT_CLICK # Amount
+                               , NavInsID.RIGHT_CLICK # Account
                                , NavInsID.RIGHT_CLICK # Max Gas
+                               , NavInsID.RIGHT_CLICK # Digest
                                , NavInsID.RIGHT_CLICK # Sign Transaction?
                                , NavInsID.RIGHT_CLICK # Confirm
                                , NavInsID.BOTH_CLICK
@@ -146,7 +1007,7 @@ def test_sign_tx_refused(backend, scenario_navigator, firmware, navigator):
 # should reject signing a non-SUI coin transaction, if blind signing is not enabled
 def test_sign_tx_non_sui_transfer_rejected(backend, scenario_navigator, firmware, navigator):
     client = Client(backend, use_block_protocol=True)
-    path = "m/44'/784'/0'"
+    path = "m/44'/784'/0'/0'/0'"
 
     _, public_key, _, _ = client.get_public_key(path=path)
     assert len(public_key) == 32
@@ -183,7 +1044,7 @@ def test_sign_tx_non_sui_transfer_rejected(backend, scenario_navigator, firmware
 # should reject signing an unknown transaction, if blind signing is not enabled
 def test_sign_tx_unknown_tx_rejected(backend, scenario_navigator, firmware, navigator):
     client = Client(backend, use_block_protocol=True)
-    path = "m/44'/784'/0'"
+    path = "m/44'/784'/0'/0'/0'"
 
     _, public_key, _, _ = client.get_public_key(path=path)
     assert len(public_key) == 32
@@ -217,6 +1078,236 @@ def test_sign_tx_unknown_tx_rejected(backend, scenario_navigator, firmware, navi
 
     assert len(e.value.data) == 0
 
+# should reject a transfer that moves the gas coin instead of the split coin, if blind signing is not enabled
+def test_sign_tx_transfer_not_split_coin_rejected(backend, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    # SplitCoins(GasCoin, [Input(0)]) followed by TransferObjects([GasCoin], Input(1))
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b02020001010000010100000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000')
+
+    sign_tx_not_recognized(client, firmware, navigator, path, transaction)
+
+# refuses to clear-sign a transaction whose sender is not the address of the signing key
+def test_sign_tx_sender_mismatch_rejected(backend, firmware):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/1'/0'/0'"
+
+    # A SUI transfer sent from 0x6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000')
+
+    with pytest.raises(ExceptionRAPDU) as e:
+        client.sign_tx(path=path, transaction=transaction)
+
+    assert e.value.status == Errors.SW_SENDER_MISMATCH
+
+# warns before reviewing with a path of a form Sui wallets don't derive, then signs only if
+# the review is approved
+@pytest.mark.parametrize("approve", [True, False])
+def test_sign_tx_unusual_path(backend, firmware, navigator, approve):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # A SUI transfer sent from 0x56b19e720f3bfa8caaef806afdd5dfaffd0d6ec9476323a14d1638ad734b2ba5
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b02020001010000010102000001010056b19e720f3bfa8caaef806afdd5dfaffd0d6ec9476323a14d1638ad734b2ba50112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d56b19e720f3bfa8caaef806afdd5dfaffd0d6ec9476323a14d1638ad734b2ba501000000000000000c0400000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_with_unusual_path(firmware, navigator, approve)
+
+    def check_result(result):
+        if not approve:
+            pytest.fail('should not happen')
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    if approve:
+        run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+    else:
+        with pytest.raises(ExceptionRAPDU) as e:
+            run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+        assert len(e.value.data) == 0
+
+# signs with a path of Sui's standard form without warning about it
+def test_sign_tx_standard_path(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    # A SUI transfer sent from 0x6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e21
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000')
+
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^From$", "^0x6fb21fee"])
+
+    def check_result(result):
+        assert len(result) == 64
+        assert check_signature_validity(public_key, result, transaction)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# can sign a printable personal message
+def test_sign_personal_message(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    message = b"Hello, Sui"
+
+    def apdu_task():
+        return client.sign_personal_message(path=path, message=message)
+
+    def nav_task():
+        if firmware.device.startswith("nano"):
+            navigator.navigate([ NavInsID.RIGHT_CLICK # Sign Personal Message
+                                 , NavInsID.RIGHT_CLICK, NavInsID.RIGHT_CLICK # From ...
+                                 , NavInsID.RIGHT_CLICK # Account
+                                 , NavInsID.RIGHT_CLICK # Message
+                                 , NavInsID.RIGHT_CLICK # Sign Message?
+                                 , NavInsID.BOTH_CLICK
+                                ]
+                               , timeout=10
+                               , screen_change_before_first_instruction=True
+                               , screen_change_after_last_instruction=False)
+        else:
+            scenario_navigator.review_approve(do_comparison=False)
+
+    def check_result(result):
+        assert len(result) == 64
+        # Intent for personal messages, then the message as a BCS vector<u8>
+        signed = bytes([3, 0, 0, len(message)]) + message
+        assert check_signature_validity(public_key, result, signed)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# can sign a personal message with secp256k1 and secp256r1 keys, and serialize the signature
+@pytest.mark.parametrize("path,curve,flag", [("m/54'/784'/0'/0/0", SECP256k1, 0x01), ("m/74'/784'/0'/0/0", NIST256p, 0x02)])
+def test_sign_personal_message_ecdsa(backend, scenario_navigator, firmware, navigator, path, curve, flag):
+    client = Client(backend, use_block_protocol=True)
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    message = b"Hello, Sui"
+
+    def apdu_task():
+        return client.sign_personal_message(path=path, message=message, serialized=True)
+
+    def nav_task():
+        review_showing(firmware, navigator, ["^Message$", "^Hello, Sui"])
+
+    def check_result(result):
+        assert len(result) == 1 + 64 + 33
+        assert result[0] == flag
+        signed = bytes([3, 0, 0, len(message)]) + message
+        assert check_ecdsa_signature_validity(public_key, result[1:65], signed, curve)
+        assert result[65:] == public_key
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# shows a message with characters the Nano fonts lack as hex on Nano, and as text elsewhere
+def test_sign_personal_message_non_ascii(backend, scenario_navigator, firmware, navigator):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    _, public_key, _, _ = client.get_public_key(path=path)
+
+    message = "Héllo, Sui".encode()
+
+    def apdu_task():
+        return client.sign_personal_message(path=path, message=message)
+
+    def nav_task():
+        if firmware.device.startswith("nano"):
+            review_showing(firmware, navigator, ["^Message \\(hex\\)", "^48c3a96c", "^Message Hash$"])
+        else:
+            review_showing(firmware, navigator, ["^Message$", "^Héllo, Sui"])
+
+    def check_result(result):
+        assert len(result) == 64
+        signed = bytes([3, 0, 0, len(message)]) + message
+        assert check_signature_validity(public_key, result, signed)
+
+    run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+# refuses to sign a payload whose intent is not a Sui transaction
+def test_sign_tx_wrong_intent_rejected(backend, firmware):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000')
+
+    for intent, error in [(bytes([3, 0, 0]), Errors.SW_WRONG_INTENT_SCOPE),
+                          (bytes([0, 1, 0]), Errors.SW_WRONG_INTENT_VERSION),
+                          (bytes([0, 0, 1]), Errors.SW_WRONG_APP_ID)]:
+        with pytest.raises(ExceptionRAPDU) as e:
+            client.sign_tx(path=path, transaction=intent + transaction[3:])
+
+        assert e.value.status == error
+
+# refuses flags the sign instructions don't support, before showing anything
+def test_sign_unsupported_flags_rejected(backend, firmware):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000')
+
+    for flags in [0x04, 0x80, 0x07]:
+        with pytest.raises(ExceptionRAPDU) as e:
+            client.sign_tx(path=path, transaction=transaction, flags=flags)
+        assert len(e.value.data) == 0
+
+    # Personal messages have no digest to return
+    for flags in [0x01, 0x04]:
+        with pytest.raises(ExceptionRAPDU) as e:
+            client.sign_personal_message(path=path, message=b"Hello, Sui", flags=flags)
+        assert len(e.value.data) == 0
+
+# refuses to sign as sponsor a transaction whose gas is paid by its sender
+def test_sign_tx_as_sponsor_not_sponsored_rejected(backend, firmware):
+    client = Client(backend, use_block_protocol=True)
+    path = "m/44'/784'/0'/0'/0'"
+
+    transaction = bytes.fromhex('000000000002000840420f000000000000204f2370b2a4810ad6c8e1cfd92cc8c8818fef8f59e3a80cea17871f78d850ba4b0202000101000001010200000101006fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e210112a6d0c44edc630d2724b1f57fea4f93308b1d22164402c65778bd99379c4733070000000000000020f2fd3c87b227f1015182fe4348ed680d7ed32bcd3269704252c03e1d0b13d30d6fb21feead027da4873295affd6c4f3618fe176fa2fbf3e7b5ef1d9463b31e2101000000000000000c0400000000000000')
+
+    with pytest.raises(ExceptionRAPDU) as e:
+        client.sign_tx_as_sponsor(path=path, transaction=transaction)
+
+    assert len(e.value.data) == 0
+
+# Ask to sign a transaction which can't be clear-signed, dismiss the warning about it and check
+# that signing fails
+def sign_tx_not_recognized(client, firmware, navigator, path, transaction):
+    def apdu_task():
+        return client.sign_tx(path=path, transaction=transaction)
+
+    def nav_task():
+        if firmware.device.startswith("nano"):
+            navigator.navigate([NavInsID.RIGHT_CLICK, NavInsID.RIGHT_CLICK, NavInsID.RIGHT_CLICK],
+                               screen_change_before_first_instruction=True,
+                               screen_change_after_last_instruction=False)
+        else:
+            # Dismiss the "Enable Blind signing" screen
+            navigator.navigate([NavInsID.USE_CASE_CHOICE_REJECT],
+                               screen_change_before_first_instruction=False,
+                               screen_change_after_last_instruction=False)
+
+    def check_result(result):
+        pytest.fail('should not happen')
+
+    with pytest.raises(ExceptionRAPDU) as e:
+        run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result)
+
+    assert len(e.value.data) == 0
+
 @contextmanager
 def blind_sign_enabled(firmware, navigator):
     toggle_blind_sign(firmware, navigator)
@@ -6,8 +6,10 @@ from pathlib import Path
 from hashlib import blake2b
 from hashlib import sha256
 
-from ecdsa.curves import Ed25519
+from ecdsa.curves import Curve, Ed25519
 from ecdsa.keys import VerifyingKey
+from ecdsa.util import sigdecode_string
+from ragger.navigator import NavInsID
 
 
 ROOT_SCREENSHOT_PATH = Path(__file__).parent.resolve()
@@ -26,6 +28,81 @@ def check_signature_validity(public_key: bytes, signature: bytes, message: bytes
         data=hash_object.digest()
     )
 
+# Check if an ECDSA signature of a given message is valid, as Sui verifies it: over the SHA-256
+# hash of the Blake2b digest, and with a low s
+def check_ecdsa_signature_validity(public_key: bytes, signature: bytes, message: bytes, curve: Curve) -> bool:
+    pk: VerifyingKey = VerifyingKey.from_string(public_key, curve=curve)
+    hash_object = blake2b(digest_size=32)
+    hash_object.update(message)
+    s = int.from_bytes(signature[32:], byteorder='big')
+    return s <= curve.order // 2 and pk.verify(
+        signature=signature,
+        data=hash_object.digest(),
+        hashfunc=sha256,
+        sigdecode=sigdecode_string
+    )
+
+# Continue past the "Unusual derivation path" warning shown before reviews with paths like
+# m/44'/784'/0', then approve or reject the review
+def review_with_unusual_path(firmware, navigator, approve=True):
+    if firmware.device.startswith("nano"):
+        review_showing(firmware, navigator, ["^WARNING$", "^Path$"], approve)
+        return
+    navigator.navigate([NavInsID.USE_CASE_CHOICE_CONFIRM]
+                       , timeout=20
+                       , screen_change_before_first_instruction=True
+                       , screen_change_after_last_instruction=True)
+    # Like scenario_navigator.review_approve() and review_reject(), without waiting for a
+    # screen change first, which already happened
+    if approve:
+        navigator.navigate_until_text(
+            navigate_instruction=NavInsID.SWIPE_CENTER_TO_LEFT
+            , validation_instructions=[NavInsID.USE_CASE_REVIEW_CONFIRM, NavInsID.USE_CASE_STATUS_DISMISS]
+            , text="^Hold to sign$"
+            , timeout=20
+            , screen_change_before_first_instruction=False
+            , screen_change_after_last_instruction=True)
+    else:
+        navigator.navigate([NavInsID.USE_CASE_REVIEW_REJECT, NavInsID.USE_CASE_CHOICE_CONFIRM, NavInsID.USE_CASE_STATUS_DISMISS]
+                           , timeout=20
+                           , screen_change_before_first_instruction=False
+                           , screen_change_after_last_instruction=True)
+
+# Go through a review, stopping at each of the texts in order to check that it is shown, then
+# approve or reject it. Texts are regular expressions matched against the start of screen lines.
+# Set screen_change_before_first_instruction to False when the review is already shown.
+def review_showing(firmware, navigator, texts, approve=True, screen_change_before_first_instruction=True):
+    nano = firmware.device.startswith("nano")
+    for i, text in enumerate(texts):
+        navigator.navigate_until_text(
+            navigate_instruction=NavInsID.RIGHT_CLICK if nano else NavInsID.SWIPE_CENTER_TO_LEFT
+            , validation_instructions=[]
+            , text=text
+            , timeout=20
+            , screen_change_before_first_instruction=screen_change_before_first_instruction and i == 0
+            , screen_change_after_last_instruction=False)
+    if nano:
+        navigator.navigate_until_text(
+            navigate_instruction=NavInsID.RIGHT_CLICK
+            , validation_instructions=[NavInsID.BOTH_CLICK]
+            , text="^Approve$" if approve else "^Reject$"
+            , timeout=20
+            , screen_change_before_first_instruction=screen_change_before_first_instruction and not texts
+            , screen_change_after_last_instruction=False)
+    elif approve:
+        navigator.navigate_until_text(
+            navigate_instruction=NavInsID.SWIPE_CENTER_TO_LEFT
+            , validation_instructions=[NavInsID.USE_CASE_REVIEW_CONFIRM, NavInsID.USE_CASE_STATUS_DISMISS]
+            , text="^Hold to sign$"
+            , timeout=20
+            , screen_change_before_first_instruction=screen_change_before_first_instruction and not texts
+            , screen_change_after_last_instruction=True)
+    else:
+        navigator.navigate([NavInsID.USE_CASE_REVIEW_REJECT, NavInsID.USE_CASE_CHOICE_CONFIRM, NavInsID.USE_CASE_STATUS_DISMISS]
+                           , timeout=20
+                           , screen_change_before_first_instruction=screen_change_before_first_instruction and not texts
+                           , screen_change_after_last_instruction=True)
+
 # Run APDU and navigation tasks concurrently
 def run_apdu_and_nav_tasks_concurrently(apdu_task, nav_task, check_result):
     executor = concurrent.futures.ThreadPoolExecutor(max_workers=2)
@@ -9,7 +9,7 @@ do
     nix-shell -A $DEVICE.rustShell --run " \
       set -x
       cd rust-app; \
-      cargo build --release --target=\$TARGET_JSON; \
+      cargo build --release --target=\$TARGET_JSON --features descriptor_test_key; \
       cd ..; \
       pytest ragger-tests --tb=short -v --device ${DEVICE/nanosplus/nanosp} ${pytest_args};
     "
@@ -0,0 +1,50 @@
+/// A coin type whose symbol and decimals we know, so amounts can be shown in its units
+pub struct KnownCoin {
+    /// The Move type of the coin, as rendered by the TypeTag parser, i.e. with addresses
+    /// stripped of their leading zeros
+    pub type_tag: &'static str,
+    pub symbol: &'static str,
+    /// At most 19, so that 10^decimals fits in a u64
+    pub decimals: u32,
+}
+
+pub static SUI_COIN: KnownCoin = KnownCoin {
+    type_tag: "0x2::sui::SUI",
+    symbol: "SUI",
+    decimals: 9,
+};
+
+pub static KNOWN_COINS: [KnownCoin; 5] = [
+    KnownCoin {
+        type_tag: "0xdba34672e30cb065b1f93e3ab55318768fd6fef66c15942c9f7cb846e2f900e7::usdc::USDC",
+        symbol: "USDC",
+        decimals: 6,
+    },
+    KnownCoin {
+        type_tag: "0xc060006111016b8a020ad5b33834984a437aaa7d3c74c18e09a95d48aceab08c::coin::COIN",
+        symbol: "USDT",
+        decimals: 6,
+    },
+    KnownCoin {
+        type_tag: "0xaf8cd5edc19c4512f4259f0bee101a40d41ebed738ade5874359610ef8eeced5::coin::COIN",
+        symbol: "WETH",
+        decimals: 8,
+    },
+    KnownCoin {
+        type_tag: "0xdeeb7a4662eec9f2f3def03fb937a663dddaa2e215b8078a284d026b7946c270::deep::DEEP",
+        symbol: "DEEP",
+        decimals: 6,
+    },
+    KnownCoin {
+        type_tag: "0x6864a6f921804860930db6ddbe2e16acdf8504495ea7481637a1c8b9a8fe54b::cetus::CETUS",
+        symbol: "CETUS",
+        decimals: 9,
+    },
+];
+
+pub fn find_known_coin(type_tag: &str) -> Option<&'static KnownCoin> {
+    if type_tag == SUI_COIN.type_tag {
+        return Some(&SUI_COIN);
+    }
+    KNOWN_COINS.iter().find(|coin| coin.type_tag == type_tag)
+}
@@ -0,0 +1,408 @@
+use crate::handle_apdu::ProvidedForNextTx;
+use crate::implementation::{
+    Coin, CoinAmount, IdentifierString, MoveCall, TransferAmount, IDENTIFIER_LENGTH,
+    MOVE_CALL_ARGUMENTS_LENGTH,
+};
+use crate::interface::*;
+use crate::pure::*;
+use alamgu_async_block::*;
+use arrayvec::{ArrayString, ArrayVec};
+use core::convert::TryInto;
+use ledger_crypto_helpers::hasher::{Hasher, SHA256};
+use ledger_device_sdk::ecc::{CurvesId, ECPublicKey};
+use ledger_device_sdk::io::SyscallError;
+use ledger_log::trace;
+use ledger_parser_combinators::async_parser::*;
+use ledger_secure_sdk_sys::{
+    cx_ecfp_384_public_key_t, os_pki_get_info, os_pki_load_certificate, os_pki_verify,
+};
+
+pub const DESCRIPTOR_VERSION: u8 = 1;
+pub const DESCRIPTOR_NAME_LENGTH: usize = 32;
+/// Labels are screen titles, which are short on the Nanos
+pub const DESCRIPTOR_LABEL_LENGTH: usize = 16;
+/// The longest descriptor: its version, package, module, function and name, then each argument
+/// with its label and the longest format. All lengths are below 128, so single byte ULEB128s.
+pub const DESCRIPTOR_LENGTH: usize = 1
+    + SUI_ADDRESS_LENGTH
+    + 2 * (1 + IDENTIFIER_LENGTH)
+    + (1 + DESCRIPTOR_NAME_LENGTH)
+    + 1
+    + MOVE_CALL_ARGUMENTS_LENGTH * ((1 + DESCRIPTOR_LABEL_LENGTH) + 3);
+/// DER encoded ECDSA signatures are at most 72 bytes
+pub const DESCRIPTOR_SIGNATURE_LENGTH: usize = 72;
+/// Prefix of what descriptor signatures are over, so that no signature made for something else
+/// by the same key is a valid descriptor signature
+pub const DESCRIPTOR_SIGNING_DOMAIN: &[u8] = b"sui-descriptor";
+
+/// Key usage of the certificates of keys which sign descriptors of calls, in the Ledger PKI
+const CERTIFICATE_PUBLIC_KEY_USAGE_CALLDATA: u8 = 0x0b;
+/// Ledger certificates are a few hundred bytes at most
+pub const CERTIFICATE_LENGTH: usize = 512;
+const CERTIFICATE_TRUSTED_NAME_LENGTH: usize = 64;
+/// Trusted name of the certificates of keys signing descriptors for this app, rather than for the
+/// apps of other chains
+const CERTIFICATE_TRUSTED_NAME: &[u8] = b"Sui";
+
+/// Uncompressed secp256k1 keys trusted to sign descriptors, besides keys certified through the
+/// Ledger PKI. The test key's private key is in the ragger tests, so it must never be part of a
+/// release build.
+#[cfg(feature = "descriptor_test_key")]
+const DESCRIPTOR_SIGNING_KEYS: &[[u8; 65]] = &[[
+    0x04, 0xf4, 0x27, 0x8e, 0x98, 0xd2, 0xf0, 0x1c, 0x8f, 0xd7, 0x1f, 0x5d, 0x04, 0x31, 0x50, 0x59,
+    0x09, 0x06, 0x51, 0xd0, 0xd5, 0xbe, 0x64, 0x31, 0xa1, 0x1b, 0x9f, 0x18, 0xd4, 0x16, 0x04, 0xa9,
+    0xb1, 0x75, 0xc9, 0xec, 0xa8, 0x09, 0xbb, 0x38, 0x35, 0x98, 0xf7, 0x2b, 0x82, 0xf5, 0x88, 0xf1,
+    0xc0, 0xab, 0x44, 0x1f, 0xa8, 0x2c, 0x4d, 0x54, 0xfa, 0x42, 0xea, 0xe6, 0xd7, 0x37, 0xe7, 0x1e,
+    0x04,
+]];
+#[cfg(not(feature = "descriptor_test_key"))]
+const DESCRIPTOR_SIGNING_KEYS: &[[u8; 65]] = &[];
+
+/// How a described argument is shown
+#[derive(Clone, Copy)]
+pub enum ArgumentFormat {
+    /// An object input, shown by its ID
+    Object,
+    /// A pure address
+    Address,
+    /// A pure u64 amount of the coin which is the type argument of the call at this index, shown
+    /// in its units if it is a known coin
+    Amount { type_argument: u8 },
+    /// A pure value of the given type
+    Pure(PureType),
+}
+
+pub struct DescriptorArgument {
+    pub label: ArrayString<DESCRIPTOR_LABEL_LENGTH>,
+    pub format: ArgumentFormat,
+}
+
+/// How to clear-sign calls of a package function, signed by a trusted key
+pub struct Descriptor {
+    pub package: SuiAddressRaw,
+    pub module: IdentifierString,
+    pub function: IdentifierString,
+    /// Shown as the title of the review
+    pub name: ArrayString<DESCRIPTOR_NAME_LENGTH>,
+    /// One for each argument of the function
+    pub arguments: ArrayVec<DescriptorArgument, MOVE_CALL_ARGUMENTS_LENGTH>,
+}
+
+/// The input a MoveCall argument refers to, for descriptors to format
+pub enum ArgumentValue {
+    Pure(PureBytes),
+    Object(SuiAddressRaw),
+    /// The gas coin, results of other commands, and pure inputs too long to decode
+    Other,
+}
+
+/// An argument formatted as its descriptor says
+pub enum DescribedValue<'a> {
+    Object(SuiAddressRaw),
+    Address(SuiAddressRaw),
+    Amount(Coin, u64),
+    Pure(PureValue<'a>),
+}
+
+impl core::fmt::Display for DescribedValue<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DescribedValue::Object(id) | DescribedValue::Address(id) => {
+                write!(f, "{}", PureValue::Address(*id))
+            }
+            DescribedValue::Amount(coin, amount) => {
+                write!(f, "{}", CoinAmount(coin, TransferAmount::Amount(*amount)))
+            }
+            DescribedValue::Pure(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl Descriptor {
+    pub fn is_for(&self, call: &MoveCall) -> bool {
+        call.package == self.package && call.module == self.module && call.function == self.function
+    }
+
+    /// Format the arguments of a call this descriptor is for. Fails if they don't match the
+    /// described formats, or an amount's type argument is missing.
+    pub fn describe<'a>(
+        &'a self,
+        call: &MoveCall,
+        values: &'a [ArgumentValue],
+    ) -> Option<ArrayVec<DescribedValue<'a>, MOVE_CALL_ARGUMENTS_LENGTH>> {
+        if values.len() != self.arguments.len() {
+            return None;
+        }
+        let mut rv = ArrayVec::new();
+        for (argument, value) in self.arguments.iter().zip(values) {
+            let described = match (&argument.format, value) {
+                (ArgumentFormat::Object, ArgumentValue::Object(id)) => DescribedValue::Object(*id),
+                (ArgumentFormat::Address, ArgumentValue::Pure(bytes)) => {
+                    DescribedValue::Address(decode_pure(bytes, PureType::ADDRESS)?.as_address()?)
+                }
+                (ArgumentFormat::Amount { type_argument }, ArgumentValue::Pure(bytes)) => {
+                    let type_tag = call.type_arguments.get(*type_argument as usize)?;
+                    DescribedValue::Amount(
+                        Coin::from_type_tag(*type_tag),
+                        decode_pure(bytes, PureType::U64)?.as_u64()?,
+                    )
+                }
+                (ArgumentFormat::Pure(ty), ArgumentValue::Pure(bytes)) => {
+                    DescribedValue::Pure(decode_pure(bytes, *ty)?)
+                }
+                _ => return None,
+            };
+            rv.push(described);
+        }
+        Some(rv)
+    }
+}
+
+/// Reads a descriptor from the bytes which were hashed to sign it
+struct DescriptorReader<'a>(&'a [u8]);
+
+impl<'a> DescriptorReader<'a> {
+    fn bytes(&mut self, length: usize) -> Option<&'a [u8]> {
+        if length > self.0.len() {
+            return None;
+        }
+        let (rv, rest) = self.0.split_at(length);
+        self.0 = rest;
+        Some(rv)
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        Some(self.bytes(1)?[0])
+    }
+
+    fn uleb128(&mut self) -> Option<usize> {
+        let (value, rest) = decode_uleb128(self.0)?;
+        self.0 = rest;
+        Some(value)
+    }
+
+    /// A ULEB128 length prefixed string of at most `N` bytes, all of which are `allowed`
+    fn string<const N: usize>(&mut self, allowed: fn(&u8) -> bool) -> Option<ArrayString<N>> {
+        let length = self.uleb128()?;
+        let bytes = self.bytes(length)?;
+        if !bytes.iter().all(allowed) {
+            return None;
+        }
+        ArrayString::from(core::str::from_utf8(bytes).ok()?).ok()
+    }
+}
+
+/// Move identifiers are restricted to [A-Za-z0-9_]
+fn is_identifier_byte(b: &u8) -> bool {
+    b.is_ascii_alphanumeric() || *b == b'_'
+}
+
+/// Only printable ASCII, so the text can't fake other fields of the review
+fn is_printable_byte(b: &u8) -> bool {
+    b.is_ascii_graphic() || *b == b' '
+}
+
+/// Decode a descriptor, which must be exactly `bytes`
+fn decode_descriptor(bytes: &[u8]) -> Option<Descriptor> {
+    let mut reader = DescriptorReader(bytes);
+    if reader.byte()? != DESCRIPTOR_VERSION {
+        return None;
+    }
+    let package = reader.bytes(SUI_ADDRESS_LENGTH)?.try_into().ok()?;
+    let module = reader.string(is_identifier_byte)?;
+    let function = reader.string(is_identifier_byte)?;
+    let name: ArrayString<DESCRIPTOR_NAME_LENGTH> = reader.string(is_printable_byte)?;
+    trace!("Descriptor: {}", name.as_str());
+    let count = reader.uleb128()?;
+    let mut arguments = ArrayVec::new();
+    for _ in 0..count {
+        let label = reader.string(is_printable_byte)?;
+        let format = match reader.uleb128()? {
+            0 => ArgumentFormat::Object,
+            1 => ArgumentFormat::Address,
+            2 => ArgumentFormat::Amount {
+                type_argument: reader.byte()?,
+            },
+            3 => {
+                let kind = match reader.byte()? {
+                    0 => PureKind::Bool,
+                    1 => PureKind::U8,
+                    2 => PureKind::U16,
+                    3 => PureKind::U32,
+                    4 => PureKind::U64,
+                    5 => PureKind::U128,
+                    6 => PureKind::U256,
+                    7 => PureKind::Address,
+                    8 => PureKind::Bytes,
+                    9 => PureKind::String,
+                    _ => return None,
+                };
+                let optional = match reader.byte()? {
+                    0 => false,
+                    1 => true,
+                    _ => return None,
+                };
+                ArgumentFormat::Pure(PureType { kind, optional })
+            }
+            _ => return None,
+        };
+        arguments
+            .try_push(DescriptorArgument { label, format })
+            .ok()?;
+    }
+    // Anything after the descriptor would be signed without being reviewed
+    if !reader.0.is_empty() {
+        return None;
+    }
+    Some(Descriptor {
+        package,
+        module,
+        function,
+        name,
+        arguments,
+    })
+}
+
+/// Whether the certificate last loaded with `provide_certificate_apdu` is for signing
+/// descriptors of Sui calls, and its key signed `hash`
+fn certificate_verifies(hash: &[u8; 32], signature: &[u8]) -> bool {
+    let mut key_usage: u8 = 0;
+    let mut trusted_name = [0u8; CERTIFICATE_TRUSTED_NAME_LENGTH];
+    let mut trusted_name_length: usize = 0;
+    let mut public_key: cx_ecfp_384_public_key_t = unsafe { core::mem::zeroed() };
+    let err = unsafe {
+        os_pki_get_info(
+            &mut key_usage,
+            trusted_name.as_mut_ptr(),
+            &mut trusted_name_length,
+            &mut public_key,
+        )
+    };
+    if err != 0
+        || key_usage != CERTIFICATE_PUBLIC_KEY_USAGE_CALLDATA
+        || trusted_name.get(..trusted_name_length) != Some(CERTIFICATE_TRUSTED_NAME)
+    {
+        return false;
+    }
+    let mut hash = *hash;
+    let mut signature: ArrayVec<u8, DESCRIPTOR_SIGNATURE_LENGTH> =
+        signature.iter().copied().collect();
+    unsafe {
+        os_pki_verify(
+            hash.as_mut_ptr(),
+            hash.len(),
+            signature.as_mut_ptr(),
+            signature.len(),
+        )
+    }
+}
+
+fn verify_descriptor_signature(hash: &[u8; 32], signature: &[u8]) -> bool {
+    certificate_verifies(hash, signature)
+        || DESCRIPTOR_SIGNING_KEYS.iter().any(|key| {
+            let mut public_key = ECPublicKey::<65, 'W'>::new(CurvesId::Secp256k1);
+            public_key.pubkey = *key;
+            public_key.keylength = key.len();
+            public_key.verify(
+                (signature, signature.len() as u32),
+                hash,
+                CurvesId::Secp256k1,
+            )
+        })
+}
+
+static PROVIDED_DESCRIPTOR: ProvidedForNextTx<Descriptor> = ProvidedForNextTx::new();
+
+/// The descriptor provided since the last signed transaction or message, if any. It only applies
+/// to one transaction.
+pub fn take_descriptor() -> Option<Descriptor> {
+    PROVIDED_DESCRIPTOR.take()
+}
+
+/// Check the signature of a descriptor and keep it to review the next transaction
+pub async fn provide_descriptor_apdu(io: HostIO) {
+    let mut input = match io.get_params::<2>() {
+        Some(v) => v,
+        None => reject(SyscallError::InvalidParameter as u16).await,
+    };
+
+    // Read length, and move input[0] by four bytes
+    let length = usize::from_le_bytes(input[0].read().await);
+
+    // Only what is hashed, so signed, is parsed
+    let mut bytes = ArrayVec::<u8, DESCRIPTOR_LENGTH>::new();
+    for _ in 0..length {
+        let [b]: [u8; 1] = input[0].read().await;
+        if bytes.try_push(b).is_err() {
+            reject::<()>(SyscallError::InvalidParameter as u16).await;
+        }
+    }
+
+    let hash: [u8; 32] = {
+        let mut hasher: SHA256 = Hasher::new();
+        hasher.update(DESCRIPTOR_SIGNING_DOMAIN);
+        hasher.update(&bytes);
+        hasher.finalize()
+    };
+
+    let descriptor = match decode_descriptor(&bytes) {
+        Some(descriptor) => descriptor,
+        None => reject(SyscallError::InvalidParameter as u16).await,
+    };
+
+    let [signature_length]: [u8; 1] = input[1].read().await;
+    let mut signature = ArrayVec::<u8, DESCRIPTOR_SIGNATURE_LENGTH>::new();
+    for _ in 0..signature_length {
+        let [b]: [u8; 1] = input[1].read().await;
+        if signature.try_push(b).is_err() {
+            reject::<()>(SyscallError::InvalidParameter as u16).await;
+        }
+    }
+
+    if !verify_descriptor_signature(&hash, &signature) {
+        reject::<()>(AppError::WrongDescriptorSignature as u16).await;
+    }
+
+    PROVIDED_DESCRIPTOR.provide(descriptor);
+    io.result_final(&[]).await;
+}
+
+/// Load a certificate of the Ledger PKI, whose key is then trusted to sign descriptors
+pub async fn provide_certificate_apdu(io: HostIO) {
+    let mut input = match io.get_params::<1>() {
+        Some(v) => v,
+        None => reject(SyscallError::InvalidParameter as u16).await,
+    };
+
+    // Read length, and move input[0] by four bytes
+    let length = usize::from_le_bytes(input[0].read().await);
+    let mut certificate = ArrayVec::<u8, CERTIFICATE_LENGTH>::new();
+    for _ in 0..length {
+        let [b]: [u8; 1] = input[0].read().await;
+        if certificate.try_push(b).is_err() {
+            reject::<()>(SyscallError::InvalidParameter as u16).await;
+        }
+    }
+
+    // The OS checks the certificate is signed by the Ledger root, and keeps it until the app
+    // exits or another is loaded
+    let mut trusted_name = [0u8; CERTIFICATE_TRUSTED_NAME_LENGTH];
+    let mut trusted_name_length: usize = 0;
+    let mut public_key: cx_ecfp_384_public_key_t = unsafe { core::mem::zeroed() };
+    let err = unsafe {
+        os_pki_load_certificate(
+            CERTIFICATE_PUBLIC_KEY_USAGE_CALLDATA,
+            certificate.as_mut_ptr(),
+            certificate.len(),
+            trusted_name.as_mut_ptr(),
+            &mut trusted_name_length,
+            &mut public_key,
+        )
+    };
+    if err != 0 || trusted_name.get(..trusted_name_length) != Some(CERTIFICATE_TRUSTED_NAME) {
+        trace!("Certificate rejected: {}", err);
+        reject::<()>(AppError::WrongCertificate as u16).await;
+    }
+
+    io.result_final(&[]).await;
+}
@@ -1,11 +1,14 @@
+use crate::descriptor::*;
 use crate::implementation::*;
 use crate::interface::*;
+use crate::multisig::*;
 use crate::settings::*;
 use crate::ui::UserInterface;
 use crate::utils::*;
 
 use alamgu_async_block::*;
 use arrayvec::ArrayVec;
+use core::cell::RefCell;
 use core::future::Future;
 use ledger_log::trace;
 
@@ -39,7 +42,27 @@ pub fn handle_apdu_async(
             }
             Ins::Sign => {
                 trace!("Handling sign");
-                NoinlineFut(sign_apdu(io, settings, ui)).await;
+                NoinlineFut(sign_apdu(io, settings, ui, false)).await;
+            }
+            Ins::SignAsSponsor => {
+                trace!("Handling sign as sponsor");
+                NoinlineFut(sign_apdu(io, settings, ui, true)).await;
+            }
+            Ins::SignPersonalMessage => {
+                trace!("Handling sign personal message");
+                NoinlineFut(sign_personal_message_apdu(io, settings, ui)).await;
+            }
+            Ins::ProvideDescriptor => {
+                trace!("Handling provide descriptor");
+                NoinlineFut(provide_descriptor_apdu(io)).await;
+            }
+            Ins::ProvideCertificate => {
+                trace!("Handling provide certificate");
+                NoinlineFut(provide_certificate_apdu(io)).await;
+            }
+            Ins::ProvideMultisig => {
+                trace!("Handling provide multisig");
+                NoinlineFut(provide_multisig_apdu(io)).await;
             }
             Ins::GetVersionStr => {}
             Ins::Exit => ledger_device_sdk::exit_app(0),
@@ -63,3 +86,29 @@ impl<T> core::ops::DerefMut for SingleThreaded<T> {
         &mut self.0
     }
 }
+
+/// A value which an APDU provides for the next transaction only, kept between APDUs until signing
+/// the transaction takes it
+pub struct ProvidedForNextTx<T>(SingleThreaded<RefCell<Option<T>>>);
+
+impl<T> ProvidedForNextTx<T> {
+    pub const fn new() -> Self {
+        ProvidedForNextTx(SingleThreaded(RefCell::new(None)))
+    }
+
+    /// Replace the value provided before, if any
+    pub fn provide(&self, value: T) {
+        *self.0.borrow_mut() = Some(value);
+    }
+
+    /// The value provided since it was last taken, if any
+    pub fn take(&self) -> Option<T> {
+        self.0.borrow_mut().take()
+    }
+}
+
+impl<T> Default for ProvidedForNextTx<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
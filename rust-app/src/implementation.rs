@@ -1,11 +1,15 @@
+use crate::coins::*;
+use crate::descriptor::*;
 use crate::interface::*;
+use crate::keys::*;
+use crate::multisig::*;
+use crate::pure::*;
 use crate::settings::*;
 use crate::ui::*;
 use crate::utils::*;
 use alamgu_async_block::*;
-use arrayvec::ArrayVec;
-use ledger_crypto_helpers::common::{try_option, Address};
-use ledger_crypto_helpers::eddsa::{ed25519_public_key_bytes, eddsa_sign, with_public_keys};
+use arrayvec::{ArrayString, ArrayVec};
+use ledger_crypto_helpers::common::{Address, HexSlice};
 use ledger_crypto_helpers::hasher::{Blake2b, Hasher, HexHash};
 use ledger_device_sdk::io::{StatusWords, SyscallError};
 use ledger_log::trace;
@@ -14,15 +18,12 @@ use ledger_parser_combinators::bcs::async_parser::*;
 use ledger_parser_combinators::interp::*;
 
 use core::convert::TryFrom;
+use core::fmt::Write;
 use core::future::Future;
 
 pub type BipParserImplT = impl AsyncParser<Bip32Key, ByteStream, Output = ArrayVec<u32, 10>>;
 pub const BIP_PATH_PARSER: BipParserImplT = SubInterp(DefaultInterp);
 
-// Need a path of length 5, as make_bip32_path panics with smaller paths
-pub const BIP32_PREFIX: [u32; 5] =
-    ledger_device_sdk::ecc::make_bip32_path(b"m/44'/784'/123'/0'/0'");
-
 pub async fn get_address_apdu(io: HostIO, ui: UserInterface, prompt: bool) {
     let input = match io.get_params::<1>() {
         Some(v) => v,
@@ -31,31 +32,34 @@ pub async fn get_address_apdu(io: HostIO, ui: UserInterface, prompt: bool) {
 
     let path = BIP_PATH_PARSER.parse(&mut input[0].clone()).await;
 
-    if !path.starts_with(&BIP32_PREFIX[0..2]) {
-        reject::<()>(SyscallError::InvalidParameter as u16).await;
+    let scheme = match path_scheme(&path) {
+        Some(scheme) => scheme,
+        None => reject(SyscallError::InvalidParameter as u16).await,
+    };
+
+    if prompt {
+        check_path(&ui, scheme, &path).await;
     }
 
     let mut rv = ArrayVec::<u8, 220>::new();
 
-    if with_public_keys(&path, true, |key, address: &SuiPubKeyAddress| {
-        try_option(|| -> Option<()> {
-            if prompt {
-                ui.confirm_address(address)?;
-            }
+    if with_public_key(scheme, &path, |address: &SuiPubKeyAddress| {
+        if prompt {
+            ui.confirm_address(address)?;
+        }
 
-            let key_bytes = ed25519_public_key_bytes(key);
+        let key_bytes = address.public_key();
 
-            rv.try_push(u8::try_from(key_bytes.len()).ok()?).ok()?;
-            rv.try_extend_from_slice(key_bytes).ok()?;
+        rv.try_push(u8::try_from(key_bytes.len()).ok()?).ok()?;
+        rv.try_extend_from_slice(key_bytes).ok()?;
 
-            // And we'll send the address along;
-            let binary_address = address.get_binary_address();
-            rv.try_push(u8::try_from(binary_address.len()).ok()?).ok()?;
-            rv.try_extend_from_slice(binary_address).ok()?;
-            Some(())
-        }())
+        // And we'll send the address along;
+        let binary_address = address.get_binary_address();
+        rv.try_push(u8::try_from(binary_address.len()).ok()?).ok()?;
+        rv.try_extend_from_slice(binary_address).ok()?;
+        Some(())
     })
-    .is_err()
+    .is_none()
     {
         reject::<()>(StatusWords::UserCancelled as u16).await;
     }
@@ -63,11 +67,21 @@ pub async fn get_address_apdu(io: HostIO, ui: UserInterface, prompt: bool) {
     io.result_final(&rv).await;
 }
 
+/// Warn about paths which don't have Sui's standard form, before any review with them
+async fn check_path(ui: &UserInterface, scheme: SignatureScheme, path: &[u32]) {
+    if !is_standard_path(scheme, path) && ui.warn_unusual_path(path).is_none() {
+        reject::<()>(StatusWords::UserCancelled as u16).await;
+    }
+}
+
 pub enum CallArg {
-    RecipientAddress(SuiAddressRaw),
-    Amount(u64),
+    /// Pure values are decoded according to how they are used
+    Pure(PureBytes),
+    /// A pure value longer than PURE_VALUE_LENGTH
     OtherPure,
-    ObjectArg,
+    ImmOrOwnedObject(SuiAddressRaw),
+    SharedObject(SuiAddressRaw),
+    ReceivingObject(SuiAddressRaw),
 }
 
 impl HasOutput<CallArgSchema> for DefaultInterp {
@@ -89,27 +103,15 @@ impl<BS: Clone + Readable> AsyncParser<CallArgSchema, BS> for DefaultInterp {
                         <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input)
                             .await;
                     trace!("CallArgSchema: Pure: length: {}", length);
-                    match length {
-                        8 => CallArg::Amount(
-                            <DefaultInterp as AsyncParser<Amount, BS>>::parse(
-                                &DefaultInterp,
-                                input,
-                            )
-                            .await,
-                        ),
-                        32 => CallArg::RecipientAddress(
-                            <DefaultInterp as AsyncParser<Recipient, BS>>::parse(
-                                &DefaultInterp,
-                                input,
-                            )
-                            .await,
-                        ),
-                        _ => {
-                            for _ in 0..length {
-                                let _: [u8; 1] = input.read().await;
-                            }
-                            CallArg::OtherPure
-                        }
+                    let mut bytes = PureBytes::new();
+                    for _ in 0..length {
+                        let [b]: [u8; 1] = input.read().await;
+                        let _ = bytes.try_push(b);
+                    }
+                    if length as usize <= PURE_VALUE_LENGTH {
+                        CallArg::Pure(bytes)
+                    } else {
+                        CallArg::OtherPure
                     }
                 }
                 1 => {
@@ -119,17 +121,23 @@ impl<BS: Clone + Readable> AsyncParser<CallArgSchema, BS> for DefaultInterp {
                     match enum_variant {
                         0 => {
                             trace!("CallArgSchema: ObjectArg: ImmOrOwnedObject");
-                            object_ref_parser().parse(input).await;
+                            CallArg::ImmOrOwnedObject(object_id_parser().parse(input).await)
                         }
                         1 => {
                             trace!("CallArgSchema: ObjectArg: SharedObject");
-                            <(DefaultInterp, DefaultInterp, DefaultInterp) as AsyncParser<
-                                SharedObject,
-                                BS,
-                            >>::parse(
-                                &(DefaultInterp, DefaultInterp, DefaultInterp), input
-                            )
-                            .await;
+                            let (object_id, _, _) =
+                                <(DefaultInterp, DefaultInterp, DefaultInterp) as AsyncParser<
+                                    SharedObject,
+                                    BS,
+                                >>::parse(
+                                    &(DefaultInterp, DefaultInterp, DefaultInterp), input
+                                )
+                                .await;
+                            CallArg::SharedObject(object_id)
+                        }
+                        2 => {
+                            trace!("CallArgSchema: ObjectArg: Receiving");
+                            CallArg::ReceivingObject(object_id_parser().parse(input).await)
                         }
                         _ => {
                             reject_on(
@@ -140,7 +148,6 @@ impl<BS: Clone + Readable> AsyncParser<CallArgSchema, BS> for DefaultInterp {
                             .await
                         }
                     }
-                    CallArg::ObjectArg
                 }
                 _ => {
                     trace!("CallArgSchema: Unknown enum: {}", enum_variant);
@@ -156,12 +163,44 @@ impl<BS: Clone + Readable> AsyncParser<CallArgSchema, BS> for DefaultInterp {
     }
 }
 
-pub const TRANSFER_OBJECT_ARRAY_LENGTH: usize = 1;
+pub const TRANSFER_OBJECT_ARRAY_LENGTH: usize = 8;
 pub const SPLIT_COIN_ARRAY_LENGTH: usize = 8;
+pub const MERGE_COIN_ARRAY_LENGTH: usize = 32;
+pub const PAYOUTS_LENGTH: usize = 8;
+pub const SHARED_OBJECT_ARRAY_LENGTH: usize = 4;
+pub const RECEIVING_OBJECT_ARRAY_LENGTH: usize = 4;
+pub const MOVE_CALL_ARGUMENTS_LENGTH: usize = 8;
+pub const MOVE_CALL_TYPE_ARGUMENTS_LENGTH: usize = 3;
+pub const IDENTIFIER_LENGTH: usize = 64;
+pub const TYPE_TAG_STRING_LENGTH: usize = 128;
+pub const TYPE_TAG_MAX_DEPTH: usize = 8;
+pub const INPUT_KINDS_LENGTH: usize = 32;
+pub const PURE_INPUTS_LENGTH: usize = 16;
+pub const MOVE_VEC_LENGTH: usize = 2;
+/// Enough for a merge of the most coins, plus its destination
+pub const OWNED_OBJECT_ARRAY_LENGTH: usize = MERGE_COIN_ARRAY_LENGTH + 1;
+
+const fn short_address(last_byte: u8) -> SuiAddressRaw {
+    let mut address = [0; SUI_ADDRESS_LENGTH];
+    address[SUI_ADDRESS_LENGTH - 1] = last_byte;
+    address
+}
+
+pub const SUI_FRAMEWORK_ADDRESS: SuiAddressRaw = short_address(2);
+pub const SUI_SYSTEM_ADDRESS: SuiAddressRaw = short_address(3);
+pub const SUI_SYSTEM_STATE_OBJECT_ID: SuiAddressRaw = short_address(5);
+
+pub type IdentifierString = ArrayString<IDENTIFIER_LENGTH>;
+pub type TypeTagString = ArrayString<TYPE_TAG_STRING_LENGTH>;
 
 pub enum Command {
+    MoveCall(MoveCall),
     TransferObject(ArrayVec<Argument, TRANSFER_OBJECT_ARRAY_LENGTH>, Argument),
     SplitCoins(Argument, ArrayVec<Argument, SPLIT_COIN_ARRAY_LENGTH>),
+    MergeCoins(Argument, ArrayVec<Argument, MERGE_COIN_ARRAY_LENGTH>),
+    /// The element type is not kept, as the vector is only supported as argument of
+    /// `pay::join_vec`, which fixes it
+    MakeMoveVec(ArrayVec<Argument, MERGE_COIN_ARRAY_LENGTH>),
 }
 
 impl HasOutput<CommandSchema> for DefaultInterp {
@@ -178,6 +217,16 @@ impl<BS: Clone + Readable> AsyncParser<CommandSchema, BS> for DefaultInterp {
             let enum_variant =
                 <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
             match enum_variant {
+                0 => {
+                    trace!("CommandSchema: MoveCall");
+                    Command::MoveCall(
+                        <DefaultInterp as AsyncParser<ProgrammableMoveCallSchema, BS>>::parse(
+                            &DefaultInterp,
+                            input,
+                        )
+                        .await,
+                    )
+                }
                 1 => {
                     trace!("CommandSchema: TransferObject");
                     let v1 = <SubInterp<DefaultInterp> as AsyncParser<
@@ -206,6 +255,49 @@ impl<BS: Clone + Readable> AsyncParser<CommandSchema, BS> for DefaultInterp {
                     .await;
                     Command::SplitCoins(v1, v2)
                 }
+                3 => {
+                    trace!("CommandSchema: MergeCoins");
+                    let v1 = <DefaultInterp as AsyncParser<ArgumentSchema, BS>>::parse(
+                        &DefaultInterp,
+                        input,
+                    )
+                    .await;
+                    let v2 = <SubInterp<DefaultInterp> as AsyncParser<
+                        Vec<ArgumentSchema, MERGE_COIN_ARRAY_LENGTH>,
+                        BS,
+                    >>::parse(&SubInterp(DefaultInterp), input)
+                    .await;
+                    Command::MergeCoins(v1, v2)
+                }
+                5 => {
+                    trace!("CommandSchema: MakeMoveVec");
+                    // An optional type of the elements
+                    let [has_type]: [u8; 1] = input.read().await;
+                    match has_type {
+                        0 => {}
+                        1 => {
+                            <DefaultInterp as AsyncParser<TypeTagSchema, BS>>::parse(
+                                &DefaultInterp,
+                                input,
+                            )
+                            .await;
+                        }
+                        _ => {
+                            reject_on::<()>(
+                                core::file!(),
+                                core::line!(),
+                                SyscallError::InvalidParameter as u16,
+                            )
+                            .await
+                        }
+                    }
+                    let v = <SubInterp<DefaultInterp> as AsyncParser<
+                        Vec<ArgumentSchema, MERGE_COIN_ARRAY_LENGTH>,
+                        BS,
+                    >>::parse(&SubInterp(DefaultInterp), input)
+                    .await;
+                    Command::MakeMoveVec(v)
+                }
                 _ => {
                     trace!("CommandSchema: Unknown enum: {}", enum_variant);
                     reject_on(
@@ -220,6 +312,7 @@ impl<BS: Clone + Readable> AsyncParser<CommandSchema, BS> for DefaultInterp {
     }
 }
 
+#[derive(Clone, Copy)]
 pub enum Argument {
     GasCoin,
     Input(u16),
@@ -281,320 +374,1836 @@ impl<BS: Clone + Readable> AsyncParser<ArgumentSchema, BS> for DefaultInterp {
     }
 }
 
-impl HasOutput<ProgrammableTransaction> for ProgrammableTransaction {
-    type Output = (
-        <DefaultInterp as HasOutput<Recipient>>::Output,
-        <DefaultInterp as HasOutput<Amount>>::Output,
-    );
+pub struct MoveCall {
+    pub package: SuiAddressRaw,
+    pub module: IdentifierString,
+    pub function: IdentifierString,
+    pub type_arguments: ArrayVec<TypeTagString, MOVE_CALL_TYPE_ARGUMENTS_LENGTH>,
+    pub arguments: ArrayVec<Argument, MOVE_CALL_ARGUMENTS_LENGTH>,
 }
 
-impl<BS: Clone + Readable> AsyncParser<ProgrammableTransaction, BS> for ProgrammableTransaction {
+impl HasOutput<ProgrammableMoveCallSchema> for DefaultInterp {
+    type Output = MoveCall;
+}
+
+impl<BS: Clone + Readable> AsyncParser<ProgrammableMoveCallSchema, BS> for DefaultInterp {
     type State<'c>
         = impl Future<Output = Self::Output> + 'c
     where
         BS: 'c;
     fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
         async move {
-            let mut recipient_addr = None;
-            let mut recipient_index = None;
-            let mut amounts: ArrayVec<(u64, u32), SPLIT_COIN_ARRAY_LENGTH> = ArrayVec::new();
-
-            // Handle inputs
-            {
-                let length =
-                    <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
-
-                trace!("ProgrammableTransaction: Inputs: {}", length);
-                for i in 0..length {
-                    let arg = <DefaultInterp as AsyncParser<CallArgSchema, BS>>::parse(
-                        &DefaultInterp,
-                        input,
-                    )
+            let package =
+                <DefaultInterp as AsyncParser<ObjectID, BS>>::parse(&DefaultInterp, input).await;
+            let module =
+                <DefaultInterp as AsyncParser<IdentifierSchema, BS>>::parse(&DefaultInterp, input)
                     .await;
-                    match arg {
-                        CallArg::RecipientAddress(addr) => match recipient_addr {
-                            None => {
-                                recipient_addr = Some(addr);
-                                recipient_index = Some(i);
-                            }
-                            // Reject on multiple RecipientAddress(s)
-                            _ => {
-                                reject_on(
-                                    core::file!(),
-                                    core::line!(),
-                                    SyscallError::NotSupported as u16,
-                                )
-                                .await
-                            }
-                        },
-                        CallArg::Amount(amt) =>
-                        {
-                            #[allow(clippy::single_match)]
-                            match amounts.try_push((amt, i)) {
-                                Err(_) => {
-                                    reject_on(
-                                        core::file!(),
-                                        core::line!(),
-                                        SyscallError::NotSupported as u16,
-                                    )
-                                    .await
-                                }
-                                _ => {}
-                            }
-                        }
-                        _ => {}
-                    }
-                }
-            }
-
-            if recipient_index.is_none() || amounts.is_empty() {
-                reject_on::<()>(
-                    core::file!(),
-                    core::line!(),
-                    SyscallError::NotSupported as u16,
-                )
-                .await;
-            }
-
-            let recipient = match recipient_addr {
-                Some(addr) => addr,
-                _ => {
-                    reject_on(
-                        core::file!(),
-                        core::line!(),
-                        SyscallError::NotSupported as u16,
-                    )
-                    .await
-                }
-            };
-
-            let mut verified_recipient = false;
-            let mut total_amount: u64 = 0;
-            // Handle commands
-            {
-                let length =
-                    <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
-                trace!("ProgrammableTransaction: Commands: {}", length);
-                for _ in 0..length {
-                    let c = <DefaultInterp as AsyncParser<CommandSchema, BS>>::parse(
-                        &DefaultInterp,
-                        input,
-                    )
+            let function =
+                <DefaultInterp as AsyncParser<IdentifierSchema, BS>>::parse(&DefaultInterp, input)
                     .await;
-                    match c {
-                        Command::TransferObject(_nested_results, recipient_input) => {
-                            if verified_recipient {
-                                // Reject more than one TransferObject(s)
-                                reject_on::<()>(
-                                    core::file!(),
-                                    core::line!(),
-                                    SyscallError::NotSupported as u16,
-                                )
-                                .await;
-                            }
-                            match recipient_input {
-                                Argument::Input(inp_index) => {
-                                    if Some(inp_index as u32) != recipient_index {
-                                        trace!("TransferObject recipient mismatch");
-                                        reject_on::<()>(
-                                            core::file!(),
-                                            core::line!(),
-                                            SyscallError::NotSupported as u16,
-                                        )
-                                        .await;
-                                    }
-                                    verified_recipient = true;
-                                }
-                                _ => {
-                                    reject_on(
-                                        core::file!(),
-                                        core::line!(),
-                                        SyscallError::NotSupported as u16,
-                                    )
-                                    .await
-                                }
-                            }
-                        }
-                        Command::SplitCoins(coin, input_indices) => {
-                            match coin {
-                                Argument::GasCoin => {}
-                                _ => {
-                                    reject_on(
-                                        core::file!(),
-                                        core::line!(),
-                                        SyscallError::NotSupported as u16,
-                                    )
-                                    .await
-                                }
-                            }
-                            for arg in &input_indices {
-                                match arg {
-                                    Argument::Input(inp_index) => {
-                                        for (amt, ix) in &amounts {
-                                            if *ix == (*inp_index as u32) {
-                                                match total_amount.checked_add(*amt) {
-                                                    Some(t) => total_amount = t,
-                                                    None => {
-                                                        reject_on(
-                                                            core::file!(),
-                                                            core::line!(),
-                                                            SyscallError::InvalidParameter as u16,
-                                                        )
-                                                        .await
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
-                                    _ => {
-                                        reject_on(
-                                            core::file!(),
-                                            core::line!(),
-                                            SyscallError::NotSupported as u16,
-                                        )
-                                        .await
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-
-            if !verified_recipient {
-                reject_on::<()>(
-                    core::file!(),
-                    core::line!(),
-                    SyscallError::NotSupported as u16,
-                )
-                .await;
+            trace!(
+                "ProgrammableMoveCall: {}::{}",
+                module.as_str(),
+                function.as_str()
+            );
+            let type_arguments = <SubInterp<DefaultInterp> as AsyncParser<
+                Vec<TypeTagSchema, MOVE_CALL_TYPE_ARGUMENTS_LENGTH>,
+                BS,
+            >>::parse(&SubInterp(DefaultInterp), input)
+            .await;
+            let arguments = <SubInterp<DefaultInterp> as AsyncParser<
+                Vec<ArgumentSchema, MOVE_CALL_ARGUMENTS_LENGTH>,
+                BS,
+            >>::parse(&SubInterp(DefaultInterp), input)
+            .await;
+            MoveCall {
+                package,
+                module,
+                function,
+                type_arguments,
+                arguments,
             }
-
-            (recipient, total_amount)
         }
     }
 }
 
-impl HasOutput<TransactionKind> for TransactionKind {
-    type Output = <ProgrammableTransaction as HasOutput<ProgrammableTransaction>>::Output;
+impl HasOutput<IdentifierSchema> for DefaultInterp {
+    type Output = IdentifierString;
 }
 
-impl<BS: Clone + Readable> AsyncParser<TransactionKind, BS> for TransactionKind {
+impl<BS: Clone + Readable> AsyncParser<IdentifierSchema, BS> for DefaultInterp {
     type State<'c>
         = impl Future<Output = Self::Output> + 'c
     where
         BS: 'c;
     fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
         async move {
-            let enum_variant =
+            let length =
                 <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
-            match enum_variant {
-                0 => {
-                    trace!("TransactionKind: ProgrammableTransaction");
-                    <ProgrammableTransaction as AsyncParser<ProgrammableTransaction, BS>>::parse(
-                        &ProgrammableTransaction,
-                        input,
-                    )
-                    .await
-                }
-                _ => {
-                    trace!("TransactionKind: {}", enum_variant);
-                    reject_on(
+            let mut rv = IdentifierString::new();
+            for _ in 0..length {
+                let [b]: [u8; 1] = input.read().await;
+                // Move identifiers are restricted to [A-Za-z0-9_]
+                if !(b.is_ascii_alphanumeric() || b == b'_') || rv.try_push(b as char).is_err() {
+                    reject_on::<()>(
                         core::file!(),
                         core::line!(),
                         SyscallError::NotSupported as u16,
                     )
-                    .await
+                    .await;
                 }
             }
+            rv
         }
     }
 }
 
-impl HasOutput<TransactionExpiration> for DefaultInterp {
-    type Output = ();
+fn primitive_type_name(tag: u32) -> Option<&'static str> {
+    match tag {
+        0 => Some("bool"),
+        1 => Some("u8"),
+        2 => Some("u64"),
+        3 => Some("u128"),
+        4 => Some("address"),
+        5 => Some("signer"),
+        8 => Some("u16"),
+        9 => Some("u32"),
+        10 => Some("u256"),
+        _ => None,
+    }
 }
 
-impl<BS: Clone + Readable> AsyncParser<TransactionExpiration, BS> for DefaultInterp {
+impl HasOutput<TypeTagSchema> for DefaultInterp {
+    type Output = TypeTagString;
+}
+
+// TypeTag is a recursive type, which we can't parse recursively in an async parser. Instead we
+// parse it iteratively, keeping track of how many type parameters are still to come at each level
+// of nesting, and render it as a Move type string, e.g. "0x2::coin::Coin<0x2::sui::SUI>".
+impl<BS: Clone + Readable> AsyncParser<TypeTagSchema, BS> for DefaultInterp {
     type State<'c>
         = impl Future<Output = Self::Output> + 'c
     where
         BS: 'c;
     fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
         async move {
-            let enum_variant =
-                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
-            match enum_variant {
-                0 => {
-                    trace!("TransactionExpiration: None");
-                }
-                1 => {
-                    trace!("TransactionExpiration: Epoch");
-                    <DefaultInterp as AsyncParser<EpochId, BS>>::parse(&DefaultInterp, input).await;
-                }
-                _ => {
-                    reject_on(
+            let mut rv = TypeTagString::new();
+            let mut pending: ArrayVec<u32, TYPE_TAG_MAX_DEPTH> = ArrayVec::new();
+            loop {
+                let tag =
+                    <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+                let (ok, is_leaf) = match tag {
+                    6 => {
+                        trace!("TypeTag: Vector");
+                        (
+                            rv.try_push_str("vector<").is_ok() && pending.try_push(1).is_ok(),
+                            false,
+                        )
+                    }
+                    7 => {
+                        trace!("TypeTag: Struct");
+                        let address = <DefaultInterp as AsyncParser<SuiAddress, BS>>::parse(
+                            &DefaultInterp,
+                            input,
+                        )
+                        .await;
+                        let module = <DefaultInterp as AsyncParser<IdentifierSchema, BS>>::parse(
+                            &DefaultInterp,
+                            input,
+                        )
+                        .await;
+                        let name = <DefaultInterp as AsyncParser<IdentifierSchema, BS>>::parse(
+                            &DefaultInterp,
+                            input,
+                        )
+                        .await;
+                        let type_params = <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(
+                            &DefaultInterp,
+                            input,
+                        )
+                        .await;
+                        let ok = write!(
+                            rv,
+                            "{}::{}::{}",
+                            ShortAddress(&address),
+                            module.as_str(),
+                            name.as_str()
+                        )
+                        .is_ok();
+                        if type_params > 0 {
+                            (
+                                ok && rv.try_push('<').is_ok()
+                                    && pending.try_push(type_params).is_ok(),
+                                false,
+                            )
+                        } else {
+                            (ok, true)
+                        }
+                    }
+                    _ => match primitive_type_name(tag) {
+                        Some(name) => (rv.try_push_str(name).is_ok(), true),
+                        None => {
+                            trace!("TypeTag: Unknown enum: {}", tag);
+                            (false, false)
+                        }
+                    },
+                };
+                if !ok {
+                    reject_on::<()>(
                         core::file!(),
                         core::line!(),
                         SyscallError::NotSupported as u16,
                     )
-                    .await
+                    .await;
+                }
+                if is_leaf {
+                    // Close every vector/struct whose type parameters are now complete, or
+                    // separate this type from the next parameter of the enclosing struct.
+                    let mut ok = true;
+                    let mut done = true;
+                    while let Some(remaining) = pending.last_mut() {
+                        *remaining -= 1;
+                        if *remaining > 0 {
+                            ok = rv.try_push_str(", ").is_ok();
+                            done = false;
+                            break;
+                        }
+                        pending.pop();
+                        ok = ok && rv.try_push('>').is_ok();
+                    }
+                    if !ok {
+                        reject_on::<()>(
+                            core::file!(),
+                            core::line!(),
+                            SyscallError::NotSupported as u16,
+                        )
+                        .await;
+                    }
+                    if done {
+                        break rv;
+                    }
                 }
             }
         }
     }
 }
 
-const fn gas_data_parser<BS: Clone + Readable>() -> impl AsyncParser<GasData, BS, Output = u64> {
-    Action(
-        (
-            SubInterp(object_ref_parser()),
-            DefaultInterp,
-            DefaultInterp,
-            DefaultInterp,
-        ),
-        |(_, _sender, _gas_price, gas_budget): (_, _, u64, u64)| {
-            // Gas price is per gas amount. Gas budget is total, reflecting the amount of gas *
-            // gas price. We only care about the total, not the price or amount in isolation , so we
-            // just ignore that field.
-            //
-            // C.F. https://github.com/MystenLabs/sui/pull/8676
-            Some(gas_budget)
-        },
-    )
+#[derive(Clone, Copy)]
+pub enum InputKind {
+    Pure,
+    Object,
+    Receiving,
 }
 
-const fn object_ref_parser<BS: Readable>() -> impl AsyncParser<ObjectRef, BS, Output = ()> {
-    Action((DefaultInterp, DefaultInterp, DefaultInterp), |_| Some(()))
+/// Where an argument of a command comes from, for display
+#[derive(Clone, Copy)]
+pub enum ArgumentOrigin {
+    GasCoin,
+    PureInput(u16),
+    ObjectInput(u16),
+    ReceivingInput(u16),
+    Result(u16),
+    NestedResult(u16, u16),
 }
 
-const fn intent_parser<BS: Readable>() -> impl AsyncParser<Intent, BS, Output = ()> {
-    Action((DefaultInterp, DefaultInterp, DefaultInterp), |_| {
-        trace!("Intent Ok");
-        Some(())
-    })
+impl core::fmt::Display for ArgumentOrigin {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ArgumentOrigin::GasCoin => write!(f, "Gas coin"),
+            ArgumentOrigin::PureInput(i) => write!(f, "Input {i} (pure value)"),
+            ArgumentOrigin::ObjectInput(i) => write!(f, "Input {i} (object)"),
+            ArgumentOrigin::ReceivingInput(i) => write!(f, "Input {i} (received object)"),
+            ArgumentOrigin::Result(c) => write!(f, "Result of command {c}"),
+            ArgumentOrigin::NestedResult(c, n) => write!(f, "Result {n} of command {c}"),
+        }
+    }
 }
 
-type TransactionDataV1Output = (<TransactionKind as HasOutput<TransactionKind>>::Output, u64);
+/// An argument of a Move call, for display
+#[derive(Clone)]
+pub enum MoveCallArgument {
+    /// A pure input passed to a parameter of a known type, which it decodes as
+    Value(PureBytes, PureType),
+    /// A pure input passed to a parameter whose type we don't know, shown as its BCS bytes
+    Bytes(PureBytes),
+    Origin(ArgumentOrigin),
+}
 
-const fn transaction_data_v1_parser<BS: Clone + Readable>(
+impl core::fmt::Display for MoveCallArgument {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            // Values are only kept if they decode
+            MoveCallArgument::Value(bytes, ty) => match decode_pure(bytes, *ty) {
+                Some(value) => write!(f, "{value}"),
+                None => Err(core::fmt::Error),
+            },
+            MoveCallArgument::Bytes(bytes) => write!(f, "0x{}", HexSlice(bytes)),
+            MoveCallArgument::Origin(origin) => write!(f, "{origin}"),
+        }
+    }
+}
+
+fn argument_origin(arg: Argument, input_kinds: &[InputKind]) -> Option<ArgumentOrigin> {
+    match arg {
+        Argument::GasCoin => Some(ArgumentOrigin::GasCoin),
+        Argument::Input(i) => input_kinds.get(i as usize).map(|kind| match kind {
+            InputKind::Pure => ArgumentOrigin::PureInput(i),
+            InputKind::Object => ArgumentOrigin::ObjectInput(i),
+            InputKind::Receiving => ArgumentOrigin::ReceivingInput(i),
+        }),
+        Argument::Result(c) => Some(ArgumentOrigin::Result(c)),
+        Argument::NestedResult(c, n) => Some(ArgumentOrigin::NestedResult(c, n)),
+    }
+}
+
+/// The coin a `MergeCoins` adds value to
+#[derive(Clone, Copy)]
+pub enum MergeDestination {
+    GasCoin,
+    Object(SuiAddressRaw),
+}
+
+impl core::fmt::Display for MergeDestination {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            MergeDestination::GasCoin => write!(f, "Gas coin"),
+            MergeDestination::Object(id) => write!(f, "0x{}", HexSlice(id)),
+        }
+    }
+}
+
+/// A `MergeCoins` of owned coin objects into the gas coin or another owned coin
+#[derive(Clone, Copy)]
+pub struct CoinMerge {
+    pub destination: MergeDestination,
+    pub coins: usize,
+}
+
+impl core::fmt::Display for CoinMerge {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.coins {
+            1 => write!(f, "1 coin into {}", self.destination),
+            n => write!(f, "{n} coins into {}", self.destination),
+        }
+    }
+}
+
+/// A coin produced by a `SplitCoins` of the gas coin, tracked until a `TransferObjects` moves it
+struct SplitCoinResult {
+    command: u32,
+    nested: u16,
+    amount: u64,
+    transferred: bool,
+}
+
+/// Mark the coin referred to by `Result(command)` / `NestedResult(command, nested)` as transferred
+/// and return its amount. A plain `Result` is only accepted if the command produced a single coin.
+/// Returns `None` for unknown or already transferred coins.
+fn take_split_coin_result(
+    split_coins: &mut [SplitCoinResult],
+    command: u16,
+    nested: Option<u16>,
+) -> Option<u64> {
+    let command = command as u32;
+    let nested = match nested {
+        Some(n) => n,
+        None => {
+            if split_coins.iter().filter(|s| s.command == command).count() != 1 {
+                return None;
+            }
+            0
+        }
+    };
+    let coin = split_coins
+        .iter_mut()
+        .find(|s| s.command == command && s.nested == nested)?;
+    if coin.transferred {
+        return None;
+    }
+    coin.transferred = true;
+    Some(coin.amount)
+}
+
+#[derive(Clone, Copy)]
+pub enum TransferAmount {
+    Amount(u64),
+    /// The gas coin itself is transferred, i.e. the entire SUI balance minus the gas fee
+    EntireBalance,
+}
+
+/// The type of the coins moved by a transaction
+pub enum Coin {
+    Known(&'static KnownCoin),
+    /// Amounts of coins not in the registry are shown in raw units, along with the full type
+    Unknown(TypeTagString),
+}
+
+impl Coin {
+    pub fn symbol(&self) -> &str {
+        match self {
+            Coin::Known(coin) => coin.symbol,
+            Coin::Unknown(_) => "Coin",
+        }
+    }
+
+    pub fn from_type_tag(type_tag: TypeTagString) -> Coin {
+        match find_known_coin(type_tag.as_str()) {
+            Some(coin) => Coin::Known(coin),
+            None => Coin::Unknown(type_tag),
+        }
+    }
+}
+
+pub struct CoinAmount<'a>(pub &'a Coin, pub TransferAmount);
+
+impl core::fmt::Display for CoinAmount<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match (self.0, self.1) {
+            (_, TransferAmount::EntireBalance) => write!(f, "Entire SUI balance (minus gas)"),
+            (Coin::Known(coin), TransferAmount::Amount(amount)) => {
+                let (quotient, remainder_str) = get_amount_with_decimals(amount, coin.decimals);
+                write!(f, "{} {quotient}.{}", coin.symbol, remainder_str.as_str())
+            }
+            (Coin::Unknown(_), TransferAmount::Amount(amount)) => write!(f, "{amount} (raw units)"),
+        }
+    }
+}
+
+/// Remember the coin type of a framework coin call, all of which must be of the same type
+fn set_coin_type(coin_type: &mut Option<TypeTagString>, type_tag: &TypeTagString) -> bool {
+    match coin_type {
+        None => {
+            *coin_type = Some(*type_tag);
+            true
+        }
+        Some(t) => t == type_tag,
+    }
+}
+
+fn is_call(call: &MoveCall, package: &SuiAddressRaw, module: &str, function: &str) -> bool {
+    call.package == *package && call.module.as_str() == module && call.function.as_str() == function
+}
+
+/// Functions of the Sui framework (0x2) which SDKs use instead of the native commands
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FrameworkCall {
+    /// `pay::split_and_transfer<T>(c: &mut Coin<T>, amount: u64, recipient: address)`, reviewed
+    /// as a transfer of `amount`
+    SplitAndTransfer,
+    /// `pay::join_vec<T>(self: &mut Coin<T>, coins: vector<Coin<T>>)`, reviewed as a
+    /// `MergeCoins` of the elements of a `MakeMoveVec`
+    JoinVec,
+    /// `coin::split<T>(self: &mut Coin<T>, split_amount: u64): Coin<T>`, whose result is tracked
+    /// like a coin from `SplitCoins`
+    Split,
+    /// `transfer::public_transfer<T: key + store>(obj: T, recipient: address)`, reviewed as a
+    /// `TransferObjects` of a single object
+    PublicTransfer,
+}
+
+struct FrameworkFunction {
+    module: &'static str,
+    function: &'static str,
+    type_arguments: usize,
+    arguments: usize,
+    call: FrameworkCall,
+}
+
+const FRAMEWORK_FUNCTIONS: [FrameworkFunction; 4] = [
+    FrameworkFunction {
+        module: "pay",
+        function: "split_and_transfer",
+        type_arguments: 1,
+        arguments: 3,
+        call: FrameworkCall::SplitAndTransfer,
+    },
+    FrameworkFunction {
+        module: "pay",
+        function: "join_vec",
+        type_arguments: 1,
+        arguments: 2,
+        call: FrameworkCall::JoinVec,
+    },
+    FrameworkFunction {
+        module: "coin",
+        function: "split",
+        type_arguments: 1,
+        arguments: 2,
+        call: FrameworkCall::Split,
+    },
+    FrameworkFunction {
+        module: "transfer",
+        function: "public_transfer",
+        type_arguments: 1,
+        arguments: 2,
+        call: FrameworkCall::PublicTransfer,
+    },
+];
+
+fn framework_call(call: &MoveCall) -> Option<FrameworkCall> {
+    FRAMEWORK_FUNCTIONS
+        .iter()
+        .find(|f| {
+            is_call(call, &SUI_FRAMEWORK_ADDRESS, f.module, f.function)
+                && call.type_arguments.len() == f.type_arguments
+                && call.arguments.len() == f.arguments
+        })
+        .map(|f| f.call)
+}
+
+/// A function whose parameter types we know, so that the pure arguments of calls to it can be
+/// shown as values. Until the app reads parameter types from package metadata, this only lists
+/// framework functions users call in transactions of their own; pure arguments of other calls are
+/// shown as bytes.
+struct KnownSignature {
+    package: SuiAddressRaw,
+    module: &'static str,
+    function: &'static str,
+    type_arguments: usize,
+    /// The types of the parameters after `TxContext` is dropped, `None` for objects
+    parameters: &'static [Option<PureType>],
+}
+
+const PURE_STRING: Option<PureType> = Some(PureType::new(PureKind::String));
+const PURE_U64: Option<PureType> = Some(PureType::U64);
+const PURE_ADDRESS: Option<PureType> = Some(PureType::ADDRESS);
+const PURE_OPTION_U64: Option<PureType> = Some(PureType {
+    kind: PureKind::U64,
+    optional: true,
+});
+
+const KNOWN_SIGNATURES: [KnownSignature; 9] = [
+    KnownSignature {
+        package: SUI_FRAMEWORK_ADDRESS,
+        module: "pay",
+        function: "split",
+        type_arguments: 1,
+        parameters: &[None, PURE_U64],
+    },
+    KnownSignature {
+        package: SUI_FRAMEWORK_ADDRESS,
+        module: "pay",
+        function: "divide_and_keep",
+        type_arguments: 1,
+        parameters: &[None, PURE_U64],
+    },
+    KnownSignature {
+        package: SUI_FRAMEWORK_ADDRESS,
+        module: "coin",
+        function: "mint_and_transfer",
+        type_arguments: 1,
+        parameters: &[None, PURE_U64, PURE_ADDRESS],
+    },
+    KnownSignature {
+        package: SUI_FRAMEWORK_ADDRESS,
+        module: "coin",
+        function: "update_name",
+        type_arguments: 1,
+        parameters: &[None, None, PURE_STRING],
+    },
+    KnownSignature {
+        package: SUI_FRAMEWORK_ADDRESS,
+        module: "coin",
+        function: "update_symbol",
+        type_arguments: 1,
+        parameters: &[None, None, PURE_STRING],
+    },
+    KnownSignature {
+        package: SUI_FRAMEWORK_ADDRESS,
+        module: "kiosk",
+        function: "set_allow_extensions",
+        type_arguments: 0,
+        parameters: &[None, None, Some(PureType::new(PureKind::Bool))],
+    },
+    KnownSignature {
+        package: SUI_FRAMEWORK_ADDRESS,
+        module: "kiosk",
+        function: "list",
+        type_arguments: 1,
+        // The ID of the listed item is serialized as an address
+        parameters: &[None, None, PURE_ADDRESS, PURE_U64],
+    },
+    KnownSignature {
+        package: SUI_FRAMEWORK_ADDRESS,
+        module: "kiosk",
+        function: "withdraw",
+        type_arguments: 0,
+        parameters: &[None, None, PURE_OPTION_U64],
+    },
+    KnownSignature {
+        package: SUI_SYSTEM_ADDRESS,
+        module: "sui_system",
+        function: "request_add_stake_mul_coin",
+        type_arguments: 0,
+        parameters: &[None, None, PURE_OPTION_U64, PURE_ADDRESS],
+    },
+];
+
+/// The parameter types of `call`, if it calls a function of `KNOWN_SIGNATURES` with as many type
+/// arguments and arguments as the function has type parameters and parameters
+fn known_parameters(call: &MoveCall) -> Option<&'static [Option<PureType>]> {
+    KNOWN_SIGNATURES
+        .iter()
+        .find(|s| {
+            is_call(call, &s.package, s.module, s.function)
+                && call.type_arguments.len() == s.type_arguments
+                && call.arguments.len() == s.parameters.len()
+        })
+        .map(|s| s.parameters)
+}
+
+fn input_bytes(inputs: &[(PureBytes, u32)], arg: Argument) -> Option<&PureBytes> {
+    match arg {
+        Argument::Input(inp_index) => inputs
+            .iter()
+            .find(|(_, ix)| *ix == inp_index as u32)
+            .map(|(bytes, _)| bytes),
+        _ => None,
+    }
+}
+
+/// Decode the pure input `arg` as the type of the parameter it is passed to
+fn input_pure(inputs: &[(PureBytes, u32)], arg: Argument, ty: PureType) -> Option<PureValue<'_>> {
+    decode_pure(input_bytes(inputs, arg)?, ty)
+}
+
+fn input_amount(inputs: &[(PureBytes, u32)], arg: Argument) -> Option<u64> {
+    input_pure(inputs, arg, PureType::U64)?.as_u64()
+}
+
+fn input_address(inputs: &[(PureBytes, u32)], arg: Argument) -> Option<SuiAddressRaw> {
+    input_pure(inputs, arg, PureType::ADDRESS)?.as_address()
+}
+
+/// Look up the value of an object input referred to by `arg`
+fn input_value<T: Copy>(inputs: &[(T, u32)], arg: Argument) -> Option<T> {
+    match arg {
+        Argument::Input(inp_index) => inputs
+            .iter()
+            .find(|(_, ix)| *ix == inp_index as u32)
+            .map(|(value, _)| *value),
+        _ => None,
+    }
+}
+
+/// What the sender is shown about every transaction: the account signing it, the gas budget,
+/// who pays for gas if that's not the sender, the transaction digest and when the transaction
+/// expires
+#[derive(Clone, Copy)]
+pub struct TxDetails {
+    pub account: Option<u32>,
+    pub gas_budget: u64,
+    pub sponsor: Option<SuiAddressRaw>,
+    /// In base58, as wallets and explorers show it
+    pub digest: ArrayString<44>,
+    pub expiration: Expiration,
+}
+
+pub enum KnownTxn {
+    TransferCoin {
+        recipient: SuiAddressRaw,
+        coin: Coin,
+        amount: TransferAmount,
+        merge: Option<CoinMerge>,
+    },
+    TransferObjects {
+        recipient: SuiAddressRaw,
+        objects: ArrayVec<SuiAddressRaw, TRANSFER_OBJECT_ARRAY_LENGTH>,
+    },
+    Payouts {
+        coin: Coin,
+        payouts: ArrayVec<(SuiAddressRaw, u64), PAYOUTS_LENGTH>,
+        total_amount: u64,
+        merge: Option<CoinMerge>,
+    },
+    MergeCoins(CoinMerge),
+    MoveCall {
+        call: MoveCall,
+        arguments: ArrayVec<MoveCallArgument, MOVE_CALL_ARGUMENTS_LENGTH>,
+        /// IDs of the objects sent to other objects which the call receives
+        received: ArrayVec<SuiAddressRaw, RECEIVING_OBJECT_ARRAY_LENGTH>,
+        /// The inputs of the arguments, in case a descriptor formats them
+        values: ArrayVec<ArgumentValue, MOVE_CALL_ARGUMENTS_LENGTH>,
+    },
+    Stake {
+        validator: SuiAddressRaw,
+        amount: u64,
+    },
+    Unstake {
+        staked_sui: SuiAddressRaw,
+    },
+}
+
+pub struct ProgrammableTransactionOutput {
+    pub known: KnownTxn,
+    pub uses_gas_coin: bool,
+}
+
+impl HasOutput<ProgrammableTransaction> for ProgrammableTransaction {
+    type Output = ProgrammableTransactionOutput;
+}
+
+impl<BS: Clone + Readable> AsyncParser<ProgrammableTransaction, BS> for ProgrammableTransaction {
+    type State<'c>
+        = impl Future<Output = Self::Output> + 'c
+    where
+        BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let mut pure_inputs: ArrayVec<(PureBytes, u32), PURE_INPUTS_LENGTH> = ArrayVec::new();
+            let mut input_kinds: ArrayVec<InputKind, INPUT_KINDS_LENGTH> = ArrayVec::new();
+            let mut owned_objects: ArrayVec<(SuiAddressRaw, u32), OWNED_OBJECT_ARRAY_LENGTH> =
+                ArrayVec::new();
+            let mut shared_objects: ArrayVec<(SuiAddressRaw, u32), SHARED_OBJECT_ARRAY_LENGTH> =
+                ArrayVec::new();
+            let mut receiving_objects: ArrayVec<
+                (SuiAddressRaw, u32),
+                RECEIVING_OBJECT_ARRAY_LENGTH,
+            > = ArrayVec::new();
+
+            // Handle inputs
+            {
+                let length =
+                    <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+
+                trace!("ProgrammableTransaction: Inputs: {}", length);
+                for i in 0..length {
+                    let arg = <DefaultInterp as AsyncParser<CallArgSchema, BS>>::parse(
+                        &DefaultInterp,
+                        input,
+                    )
+                    .await;
+                    // Inputs past INPUT_KINDS_LENGTH can't be shown as MoveCall arguments,
+                    // which is checked when they are used.
+                    let _ = input_kinds.try_push(match arg {
+                        CallArg::ImmOrOwnedObject(_) | CallArg::SharedObject(_) => {
+                            InputKind::Object
+                        }
+                        CallArg::ReceivingObject(_) => InputKind::Receiving,
+                        _ => InputKind::Pure,
+                    });
+                    match arg {
+                        CallArg::Pure(bytes) => {
+                            // Pure inputs which don't fit are rejected if a SplitCoins, a
+                            // TransferObjects or a recognized MoveCall uses them
+                            let _ = pure_inputs.try_push((bytes, i));
+                        }
+                        CallArg::ImmOrOwnedObject(id) => {
+                            // And for objects which are transferred
+                            let _ = owned_objects.try_push((id, i));
+                        }
+                        CallArg::SharedObject(id) => {
+                            // And for the system state object used by staking calls
+                            let _ = shared_objects.try_push((id, i));
+                        }
+                        CallArg::ReceivingObject(id) => {
+                            // And for objects received by a MoveCall
+                            let _ = receiving_objects.try_push((id, i));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+
+            let mut move_call = None;
+            let mut merge = None;
+            let mut payouts: ArrayVec<(SuiAddressRaw, u64), PAYOUTS_LENGTH> = ArrayVec::new();
+            let mut transferred_gas_coin = false;
+            let mut split_gas_coin = false;
+            let mut stake = None;
+            let mut unstake = None;
+            let mut coin_type: Option<TypeTagString> = None;
+            let mut transferred_objects: ArrayVec<SuiAddressRaw, TRANSFER_OBJECT_ARRAY_LENGTH> =
+                ArrayVec::new();
+            let mut split_coins: ArrayVec<SplitCoinResult, SPLIT_COIN_ARRAY_LENGTH> =
+                ArrayVec::new();
+            let mut move_vecs: ArrayVec<
+                (u32, ArrayVec<Argument, MERGE_COIN_ARRAY_LENGTH>),
+                MOVE_VEC_LENGTH,
+            > = ArrayVec::new();
+            // Handle commands
+            {
+                let length =
+                    <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+                trace!("ProgrammableTransaction: Commands: {}", length);
+                for command_index in 0..length {
+                    let c = <DefaultInterp as AsyncParser<CommandSchema, BS>>::parse(
+                        &DefaultInterp,
+                        input,
+                    )
+                    .await;
+                    // Framework calls equivalent to a native command are handled as that command
+                    let c = match c {
+                        Command::MoveCall(call) => match framework_call(&call) {
+                            Some(FrameworkCall::PublicTransfer) => {
+                                let mut objects = ArrayVec::new();
+                                objects.push(call.arguments[0]);
+                                Command::TransferObject(objects, call.arguments[1])
+                            }
+                            Some(FrameworkCall::JoinVec) => {
+                                let coins = match call.arguments[1] {
+                                    Argument::Result(cmd) => move_vecs
+                                        .iter()
+                                        .position(|(c, _)| *c == cmd as u32)
+                                        .map(|i| move_vecs.remove(i).1),
+                                    _ => None,
+                                };
+                                match coins {
+                                    Some(coins) => Command::MergeCoins(call.arguments[0], coins),
+                                    None => {
+                                        reject_on(
+                                            core::file!(),
+                                            core::line!(),
+                                            SyscallError::NotSupported as u16,
+                                        )
+                                        .await
+                                    }
+                                }
+                            }
+                            _ => Command::MoveCall(call),
+                        },
+                        c => c,
+                    };
+                    match c {
+                        Command::MoveCall(call)
+                            if framework_call(&call) == Some(FrameworkCall::SplitAndTransfer) =>
+                        {
+                            // pay::split_and_transfer<T>(c: &mut Coin<T>, amount: u64, recipient: address)
+                            let payout = match argument_origin(call.arguments[0], &input_kinds) {
+                                Some(ArgumentOrigin::ObjectInput(_)) => {
+                                    input_address(&pure_inputs, call.arguments[2])
+                                        .zip(input_amount(&pure_inputs, call.arguments[1]))
+                                }
+                                _ => None,
+                            };
+                            let ok = payout.and_then(|p| payouts.try_push(p).ok()).is_some()
+                                && set_coin_type(&mut coin_type, &call.type_arguments[0]);
+                            if !ok {
+                                reject_on::<()>(
+                                    core::file!(),
+                                    core::line!(),
+                                    SyscallError::NotSupported as u16,
+                                )
+                                .await;
+                            }
+                        }
+                        Command::MoveCall(call)
+                            if framework_call(&call) == Some(FrameworkCall::Split) =>
+                        {
+                            // coin::split<T>(self: &mut Coin<T>, split_amount: u64): Coin<T>
+                            let amount = match argument_origin(call.arguments[0], &input_kinds) {
+                                Some(ArgumentOrigin::ObjectInput(_)) => {
+                                    input_amount(&pure_inputs, call.arguments[1])
+                                }
+                                _ => None,
+                            };
+                            let ok = amount
+                                .and_then(|amount| {
+                                    split_coins
+                                        .try_push(SplitCoinResult {
+                                            command: command_index,
+                                            nested: 0,
+                                            amount,
+                                            transferred: false,
+                                        })
+                                        .ok()
+                                })
+                                .is_some()
+                                && set_coin_type(&mut coin_type, &call.type_arguments[0]);
+                            if !ok {
+                                reject_on::<()>(
+                                    core::file!(),
+                                    core::line!(),
+                                    SyscallError::NotSupported as u16,
+                                )
+                                .await;
+                            }
+                        }
+                        Command::MoveCall(call)
+                            if is_call(
+                                &call,
+                                &SUI_SYSTEM_ADDRESS,
+                                "sui_system",
+                                "request_add_stake",
+                            ) && call.type_arguments.is_empty()
+                                && call.arguments.len() == 3 =>
+                        {
+                            // sui_system::request_add_stake(wrapper: &mut SuiSystemState,
+                            //     stake: Coin<SUI>, validator_address: address)
+                            let system_state = input_value(&shared_objects, call.arguments[0]);
+                            let amount = match call.arguments[1] {
+                                Argument::Result(cmd) => {
+                                    take_split_coin_result(&mut split_coins, cmd, None)
+                                }
+                                Argument::NestedResult(cmd, nested) => {
+                                    take_split_coin_result(&mut split_coins, cmd, Some(nested))
+                                }
+                                _ => None,
+                            };
+                            let validator = input_address(&pure_inputs, call.arguments[2]);
+                            match (system_state, amount, validator) {
+                                (
+                                    Some(SUI_SYSTEM_STATE_OBJECT_ID),
+                                    Some(amount),
+                                    Some(validator),
+                                ) if stake.is_none() => {
+                                    stake = Some((validator, amount));
+                                }
+                                _ => {
+                                    reject_on(
+                                        core::file!(),
+                                        core::line!(),
+                                        SyscallError::NotSupported as u16,
+                                    )
+                                    .await
+                                }
+                            }
+                        }
+                        Command::MoveCall(call)
+                            if is_call(
+                                &call,
+                                &SUI_SYSTEM_ADDRESS,
+                                "sui_system",
+                                "request_withdraw_stake",
+                            ) && call.type_arguments.is_empty()
+                                && call.arguments.len() == 2 =>
+                        {
+                            // sui_system::request_withdraw_stake(wrapper: &mut SuiSystemState,
+                            //     staked_sui: StakedSui)
+                            let system_state = input_value(&shared_objects, call.arguments[0]);
+                            let staked_sui = input_value(&owned_objects, call.arguments[1]);
+                            match (system_state, staked_sui) {
+                                (Some(SUI_SYSTEM_STATE_OBJECT_ID), Some(staked_sui))
+                                    if unstake.is_none() =>
+                                {
+                                    unstake = Some(staked_sui);
+                                }
+                                _ => {
+                                    reject_on(
+                                        core::file!(),
+                                        core::line!(),
+                                        SyscallError::NotSupported as u16,
+                                    )
+                                    .await
+                                }
+                            }
+                        }
+                        Command::MoveCall(call) => {
+                            // Only a lone MoveCall is shown as such, other commands could
+                            // consume or produce objects we would not show.
+                            if length != 1 {
+                                reject_on::<()>(
+                                    core::file!(),
+                                    core::line!(),
+                                    SyscallError::NotSupported as u16,
+                                )
+                                .await;
+                            }
+                            let parameters = known_parameters(&call);
+                            let mut arguments = ArrayVec::new();
+                            for (i, arg) in call.arguments.iter().enumerate() {
+                                let argument = match (
+                                    argument_origin(*arg, &input_kinds),
+                                    parameters.and_then(|p| p[i]),
+                                ) {
+                                    // A pure input which doesn't decode as the type of its
+                                    // parameter is rejected
+                                    (Some(ArgumentOrigin::PureInput(_)), Some(ty)) => {
+                                        input_bytes(&pure_inputs, *arg)
+                                            .filter(|bytes| decode_pure(bytes, ty).is_some())
+                                            .map(|bytes| MoveCallArgument::Value(bytes.clone(), ty))
+                                    }
+                                    // Values are never hidden: those too long to be kept are
+                                    // rejected
+                                    (Some(ArgumentOrigin::PureInput(_)), None) => {
+                                        input_bytes(&pure_inputs, *arg)
+                                            .map(|bytes| MoveCallArgument::Bytes(bytes.clone()))
+                                    }
+                                    (origin, _) => origin.map(MoveCallArgument::Origin),
+                                };
+                                match argument.and_then(|a| arguments.try_push(a).ok()) {
+                                    Some(()) => {}
+                                    None => {
+                                        reject_on(
+                                            core::file!(),
+                                            core::line!(),
+                                            SyscallError::NotSupported as u16,
+                                        )
+                                        .await
+                                    }
+                                }
+                            }
+                            let mut received = ArrayVec::new();
+                            for arg in &call.arguments {
+                                if let Some(ArgumentOrigin::ReceivingInput(_)) =
+                                    argument_origin(*arg, &input_kinds)
+                                {
+                                    match input_value(&receiving_objects, *arg)
+                                        .and_then(|id| received.try_push(id).ok())
+                                    {
+                                        Some(()) => {}
+                                        None => {
+                                            reject_on(
+                                                core::file!(),
+                                                core::line!(),
+                                                SyscallError::NotSupported as u16,
+                                            )
+                                            .await
+                                        }
+                                    }
+                                }
+                            }
+                            let mut values = ArrayVec::new();
+                            for arg in &call.arguments {
+                                let value = match input_bytes(&pure_inputs, *arg) {
+                                    Some(bytes) => ArgumentValue::Pure(bytes.clone()),
+                                    None => input_value(&owned_objects, *arg)
+                                        .or_else(|| input_value(&shared_objects, *arg))
+                                        .or_else(|| input_value(&receiving_objects, *arg))
+                                        .map_or(ArgumentValue::Other, ArgumentValue::Object),
+                                };
+                                // There are at most as many values as arguments
+                                let _ = values.try_push(value);
+                            }
+                            move_call = Some(KnownTxn::MoveCall {
+                                call,
+                                arguments,
+                                received,
+                                values,
+                            });
+                        }
+                        Command::MergeCoins(destination, sources) => {
+                            // Only merges of owned coin inputs into the gas coin or another
+                            // owned coin input are supported, so no object the review shows
+                            // disappears into a merge.
+                            let destination = match destination {
+                                Argument::GasCoin => Some(MergeDestination::GasCoin),
+                                _ => input_value(&owned_objects, destination)
+                                    .map(MergeDestination::Object),
+                            };
+                            let sources_ok = sources
+                                .iter()
+                                .all(|source| input_value(&owned_objects, *source).is_some());
+                            match destination {
+                                Some(destination)
+                                    if sources_ok && !sources.is_empty() && merge.is_none() =>
+                                {
+                                    merge = Some(CoinMerge {
+                                        destination,
+                                        coins: sources.len(),
+                                    });
+                                }
+                                // Reject more than one MergeCoins
+                                _ => {
+                                    reject_on(
+                                        core::file!(),
+                                        core::line!(),
+                                        SyscallError::NotSupported as u16,
+                                    )
+                                    .await
+                                }
+                            }
+                        }
+                        Command::TransferObject(objects, recipient_input) => {
+                            // Reject a TransferObject without objects
+                            if objects.is_empty() {
+                                reject_on::<()>(
+                                    core::file!(),
+                                    core::line!(),
+                                    SyscallError::NotSupported as u16,
+                                )
+                                .await;
+                            }
+                            let recipient = match input_address(&pure_inputs, recipient_input) {
+                                Some(addr) => addr,
+                                None => {
+                                    trace!("TransferObject recipient is not an address input");
+                                    reject_on(
+                                        core::file!(),
+                                        core::line!(),
+                                        SyscallError::NotSupported as u16,
+                                    )
+                                    .await
+                                }
+                            };
+                            let mut amount: u64 = 0;
+                            // Every transferred object must either be a coin split from the gas
+                            // coin, or an owned object input whose ID we show. Otherwise the
+                            // review would not show what actually moves.
+                            for object in &objects {
+                                let transferred = match *object {
+                                    Argument::Result(cmd) => {
+                                        take_split_coin_result(&mut split_coins, cmd, None)
+                                            .and_then(|amt| amount.checked_add(amt))
+                                            .map(|t| amount = t)
+                                    }
+                                    Argument::NestedResult(cmd, nested) => {
+                                        take_split_coin_result(&mut split_coins, cmd, Some(nested))
+                                            .and_then(|amt| amount.checked_add(amt))
+                                            .map(|t| amount = t)
+                                    }
+                                    Argument::Input(_) => input_value(&owned_objects, *object)
+                                        .and_then(|id| transferred_objects.try_push(id).ok()),
+                                    Argument::GasCoin if !transferred_gas_coin => {
+                                        transferred_gas_coin = true;
+                                        Some(())
+                                    }
+                                    Argument::GasCoin => None,
+                                };
+                                match transferred {
+                                    Some(()) => {}
+                                    None => {
+                                        trace!("TransferObject of an unknown object");
+                                        reject_on(
+                                            core::file!(),
+                                            core::line!(),
+                                            SyscallError::NotSupported as u16,
+                                        )
+                                        .await
+                                    }
+                                }
+                            }
+                            if payouts.try_push((recipient, amount)).is_err() {
+                                reject_on::<()>(
+                                    core::file!(),
+                                    core::line!(),
+                                    SyscallError::NotSupported as u16,
+                                )
+                                .await;
+                            }
+                        }
+                        Command::MakeMoveVec(elements) => {
+                            // Only kept for a pay::join_vec, checked when it's used
+                            if move_vecs.try_push((command_index, elements)).is_err() {
+                                reject_on::<()>(
+                                    core::file!(),
+                                    core::line!(),
+                                    SyscallError::NotSupported as u16,
+                                )
+                                .await;
+                            }
+                        }
+                        Command::SplitCoins(coin, input_indices) => {
+                            match coin {
+                                Argument::GasCoin => split_gas_coin = true,
+                                _ => {
+                                    reject_on(
+                                        core::file!(),
+                                        core::line!(),
+                                        SyscallError::NotSupported as u16,
+                                    )
+                                    .await
+                                }
+                            }
+                            for (nested, arg) in input_indices.iter().enumerate() {
+                                let result = input_amount(&pure_inputs, *arg).and_then(|amount| {
+                                    split_coins
+                                        .try_push(SplitCoinResult {
+                                            command: command_index,
+                                            nested: nested as u16,
+                                            amount,
+                                            transferred: false,
+                                        })
+                                        .ok()
+                                });
+                                if result.is_none() {
+                                    reject_on::<()>(
+                                        core::file!(),
+                                        core::line!(),
+                                        SyscallError::NotSupported as u16,
+                                    )
+                                    .await;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            // A vector which isn't merged by a pay::join_vec is used in a way we don't show
+            if !move_vecs.is_empty() {
+                reject_on::<()>(
+                    core::file!(),
+                    core::line!(),
+                    SyscallError::NotSupported as u16,
+                )
+                .await;
+            }
+
+            // The gas coin can belong to a sponsor, who must know whether it is used for more
+            // than paying gas
+            let uses_gas_coin = split_gas_coin
+                || transferred_gas_coin
+                || matches!(
+                    merge,
+                    Some(CoinMerge {
+                        destination: MergeDestination::GasCoin,
+                        ..
+                    })
+                )
+                || matches!(&move_call, Some(KnownTxn::MoveCall { arguments, .. })
+                    if arguments.iter().any(|a| matches!(a, MoveCallArgument::Origin(ArgumentOrigin::GasCoin))));
+
+            let known = 'known: {
+                if let Some(txn) = move_call {
+                    break 'known txn;
+                }
+
+                if let Some(staked_sui) = unstake {
+                    // The withdrawn stake goes back to the sender, nothing else may happen
+                    if stake.is_some()
+                        || !payouts.is_empty()
+                        || !split_coins.is_empty()
+                        || merge.is_some()
+                    {
+                        reject_on::<()>(
+                            core::file!(),
+                            core::line!(),
+                            SyscallError::NotSupported as u16,
+                        )
+                        .await;
+                    }
+                    break 'known KnownTxn::Unstake { staked_sui };
+                }
+
+                if let Some((validator, amount)) = stake {
+                    // Only SUI split from the gas coin can be staked, alongside nothing else
+                    if !payouts.is_empty()
+                        || merge.is_some()
+                        || coin_type.is_some()
+                        || split_coins.iter().any(|s| !s.transferred)
+                    {
+                        reject_on::<()>(
+                            core::file!(),
+                            core::line!(),
+                            SyscallError::NotSupported as u16,
+                        )
+                        .await;
+                    }
+                    break 'known KnownTxn::Stake { validator, amount };
+                }
+
+                // Consolidating coins, without any transfer
+                if let Some(merge) = merge {
+                    if payouts.is_empty() && split_coins.is_empty() {
+                        break 'known KnownTxn::MergeCoins(merge);
+                    }
+                }
+
+                if payouts.is_empty() {
+                    reject_on::<()>(
+                        core::file!(),
+                        core::line!(),
+                        SyscallError::NotSupported as u16,
+                    )
+                    .await;
+                }
+
+                // Coins which were split off but not transferred would stay with the sender, and
+                // the shown amount would not match the transaction; we don't support that.
+                if split_coins.iter().any(|s| !s.transferred) {
+                    trace!("SplitCoins result not transferred");
+                    reject_on::<()>(
+                        core::file!(),
+                        core::line!(),
+                        SyscallError::NotSupported as u16,
+                    )
+                    .await;
+                }
+
+                // The gas coin and objects can only be sent in a single transfer
+                if (transferred_gas_coin || !transferred_objects.is_empty()) && payouts.len() != 1 {
+                    reject_on::<()>(
+                        core::file!(),
+                        core::line!(),
+                        SyscallError::NotSupported as u16,
+                    )
+                    .await;
+                }
+
+                // Coins of another type can't be mixed with SUI from the gas coin
+                let coin = match coin_type {
+                    Some(type_tag) => {
+                        if split_gas_coin || transferred_gas_coin {
+                            reject_on::<()>(
+                                core::file!(),
+                                core::line!(),
+                                SyscallError::NotSupported as u16,
+                            )
+                            .await;
+                        }
+                        Coin::from_type_tag(type_tag)
+                    }
+                    None => Coin::Known(&SUI_COIN),
+                };
+
+                let (recipient, amount) = payouts[0];
+
+                if transferred_gas_coin {
+                    // Coins split from the gas coin would be part of the entire balance anyway, so
+                    // don't bother showing them separately.
+                    if !split_coins.is_empty() || !transferred_objects.is_empty() {
+                        reject_on::<()>(
+                            core::file!(),
+                            core::line!(),
+                            SyscallError::NotSupported as u16,
+                        )
+                        .await;
+                    }
+                    break 'known KnownTxn::TransferCoin {
+                        recipient,
+                        coin,
+                        amount: TransferAmount::EntireBalance,
+                        merge,
+                    };
+                }
+
+                if !transferred_objects.is_empty() {
+                    // Don't mix coins and objects in one review. A merge could also add value to a
+                    // transferred coin object, which the object ID alone would not show.
+                    if !split_coins.is_empty() || merge.is_some() {
+                        reject_on::<()>(
+                            core::file!(),
+                            core::line!(),
+                            SyscallError::NotSupported as u16,
+                        )
+                        .await;
+                    }
+                    break 'known KnownTxn::TransferObjects {
+                        recipient,
+                        objects: transferred_objects,
+                    };
+                }
+
+                if payouts.len() == 1 {
+                    break 'known KnownTxn::TransferCoin {
+                        recipient,
+                        coin,
+                        amount: TransferAmount::Amount(amount),
+                        merge,
+                    };
+                }
+
+                let total_amount = match payouts
+                    .iter()
+                    .try_fold(0u64, |total, (_, amount)| total.checked_add(*amount))
+                {
+                    Some(total) => total,
+                    None => {
+                        reject_on(
+                            core::file!(),
+                            core::line!(),
+                            SyscallError::InvalidParameter as u16,
+                        )
+                        .await
+                    }
+                };
+
+                KnownTxn::Payouts {
+                    coin,
+                    payouts,
+                    total_amount,
+                    merge,
+                }
+            };
+
+            ProgrammableTransactionOutput {
+                known,
+                uses_gas_coin,
+            }
+        }
+    }
+}
+
+impl HasOutput<TransactionKind> for TransactionKind {
+    type Output = <ProgrammableTransaction as HasOutput<ProgrammableTransaction>>::Output;
+}
+
+impl<BS: Clone + Readable> AsyncParser<TransactionKind, BS> for TransactionKind {
+    type State<'c>
+        = impl Future<Output = Self::Output> + 'c
+    where
+        BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let enum_variant =
+                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+            match enum_variant {
+                0 => {
+                    trace!("TransactionKind: ProgrammableTransaction");
+                    <ProgrammableTransaction as AsyncParser<ProgrammableTransaction, BS>>::parse(
+                        &ProgrammableTransaction,
+                        input,
+                    )
+                    .await
+                }
+                _ => {
+                    trace!("TransactionKind: {}", enum_variant);
+                    reject_on(
+                        core::file!(),
+                        core::line!(),
+                        SyscallError::NotSupported as u16,
+                    )
+                    .await
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Expiration {
+    None,
+    Epoch(u64),
+    /// Bounds on the epoch and on the time in seconds. The chain and nonce are not shown.
+    ValidDuring {
+        min_epoch: Option<u64>,
+        max_epoch: Option<u64>,
+        min_timestamp: Option<u64>,
+        max_timestamp: Option<u64>,
+    },
+}
+
+impl Expiration {
+    pub fn is_none(&self) -> bool {
+        matches!(
+            self,
+            Expiration::None
+                | Expiration::ValidDuring {
+                    min_epoch: None,
+                    max_epoch: None,
+                    min_timestamp: None,
+                    max_timestamp: None,
+                }
+        )
+    }
+}
+
+impl core::fmt::Display for Expiration {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Expiration::None => Ok(()),
+            Expiration::Epoch(epoch) => write!(f, "Expires after epoch {epoch}"),
+            Expiration::ValidDuring {
+                min_epoch,
+                max_epoch,
+                min_timestamp,
+                max_timestamp,
+            } => {
+                let mut separator = "";
+                if let Some(epoch) = min_epoch {
+                    write!(f, "Valid from epoch {epoch}")?;
+                    separator = ", ";
+                }
+                if let Some(epoch) = max_epoch {
+                    write!(f, "{separator}Expires after epoch {epoch}")?;
+                    separator = ", ";
+                }
+                if let Some(timestamp) = min_timestamp {
+                    write!(f, "{separator}Valid from {}", Timestamp(*timestamp))?;
+                    separator = ", ";
+                }
+                if let Some(timestamp) = max_timestamp {
+                    write!(f, "{separator}Expires at {}", Timestamp(*timestamp))?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+async fn optional_u64<BS: Readable>(input: &mut BS) -> Option<u64> {
+    let [tag]: [u8; 1] = input.read().await;
+    match tag {
+        0 => None,
+        1 => Some(<DefaultInterp as AsyncParser<U64LE, BS>>::parse(&DefaultInterp, input).await),
+        _ => {
+            reject_on(
+                core::file!(),
+                core::line!(),
+                SyscallError::InvalidParameter as u16,
+            )
+            .await
+        }
+    }
+}
+
+impl HasOutput<TransactionExpiration> for DefaultInterp {
+    type Output = Expiration;
+}
+
+impl<BS: Clone + Readable> AsyncParser<TransactionExpiration, BS> for DefaultInterp {
+    type State<'c>
+        = impl Future<Output = Self::Output> + 'c
+    where
+        BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let enum_variant =
+                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+            match enum_variant {
+                0 => {
+                    trace!("TransactionExpiration: None");
+                    Expiration::None
+                }
+                1 => {
+                    trace!("TransactionExpiration: Epoch");
+                    Expiration::Epoch(
+                        <DefaultInterp as AsyncParser<EpochId, BS>>::parse(&DefaultInterp, input)
+                            .await,
+                    )
+                }
+                2 => {
+                    trace!("TransactionExpiration: ValidDuring");
+                    let min_epoch = optional_u64(input).await;
+                    let max_epoch = optional_u64(input).await;
+                    let min_timestamp = optional_u64(input).await;
+                    let max_timestamp = optional_u64(input).await;
+                    // The chain identifier, a checkpoint digest, and the nonce tell apart
+                    // otherwise identical transactions, and aren't shown
+                    let [length]: [u8; 1] = input.read().await;
+                    if length as usize != CHAIN_IDENTIFIER_LENGTH {
+                        reject_on::<()>(
+                            core::file!(),
+                            core::line!(),
+                            SyscallError::InvalidParameter as u16,
+                        )
+                        .await;
+                    }
+                    let _: [u8; CHAIN_IDENTIFIER_LENGTH] = input.read().await;
+                    let _nonce =
+                        <DefaultInterp as AsyncParser<U32LE, BS>>::parse(&DefaultInterp, input)
+                            .await;
+                    Expiration::ValidDuring {
+                        min_epoch,
+                        max_epoch,
+                        min_timestamp,
+                        max_timestamp,
+                    }
+                }
+                _ => {
+                    reject_on(
+                        core::file!(),
+                        core::line!(),
+                        SyscallError::NotSupported as u16,
+                    )
+                    .await
+                }
+            }
+        }
+    }
+}
+
+pub const GAS_PAYMENT_LENGTH: usize = 8;
+
+pub struct GasInfo {
+    /// The first payment coins, see `payment_count` for how many there are
+    pub payment: ArrayVec<SuiAddressRaw, GAS_PAYMENT_LENGTH>,
+    pub payment_count: usize,
+    pub owner: SuiAddressRaw,
+    pub budget: u64,
+}
+
+impl HasOutput<GasData> for DefaultInterp {
+    type Output = GasInfo;
+}
+
+impl<BS: Clone + Readable> AsyncParser<GasData, BS> for DefaultInterp {
+    type State<'c>
+        = impl Future<Output = Self::Output> + 'c
+    where
+        BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let payment_count =
+                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await
+                    as usize;
+            let mut payment = ArrayVec::new();
+            for _ in 0..payment_count {
+                let object_id = object_id_parser().parse(input).await;
+                // Coins which don't fit are only needed when signing as sponsor, which rejects
+                // such transactions
+                let _ = payment.try_push(object_id);
+            }
+            let owner =
+                <DefaultInterp as AsyncParser<SuiAddress, BS>>::parse(&DefaultInterp, input).await;
+            // Gas price is per gas amount. Gas budget is total, reflecting the amount of gas *
+            // gas price. We only care about the total, not the price or amount in isolation , so we
+            // just ignore that field.
+            //
+            // C.F. https://github.com/MystenLabs/sui/pull/8676
+            let _gas_price =
+                <DefaultInterp as AsyncParser<Amount, BS>>::parse(&DefaultInterp, input).await;
+            let budget =
+                <DefaultInterp as AsyncParser<Amount, BS>>::parse(&DefaultInterp, input).await;
+            GasInfo {
+                payment,
+                payment_count,
+                owner,
+                budget,
+            }
+        }
+    }
+}
+
+const fn object_id_parser<BS: Readable>() -> impl AsyncParser<ObjectRef, BS, Output = SuiAddressRaw>
+{
+    Action(
+        (DefaultInterp, DefaultInterp, DefaultInterp),
+        |(object_id, _, _): (SuiAddressRaw, u64, _)| Some(object_id),
+    )
+}
+
+// The intent is validated by check_intent, before parsing the message
+const fn intent_parser<BS: Readable>() -> impl AsyncParser<Intent, BS, Output = ()> {
+    Action((DefaultInterp, DefaultInterp, DefaultInterp), |_| {
+        trace!("Intent Ok");
+        Some(())
+    })
+}
+
+/// Reject unless the message has an intent for `scope`, in version 0 and for the Sui app
+async fn check_intent<BS: Readable>(input: &mut BS, scope: u32) {
+    let (intent_scope, version, app_id): (u32, u32, u32) =
+        <(DefaultInterp, DefaultInterp, DefaultInterp) as AsyncParser<Intent, BS>>::parse(
+            &(DefaultInterp, DefaultInterp, DefaultInterp),
+            input,
+        )
+        .await;
+    trace!("Intent: {} {} {}", intent_scope, version, app_id);
+    if intent_scope != scope {
+        reject::<()>(AppError::WrongIntentScope as u16).await;
+    }
+    if version != INTENT_VERSION_V0 {
+        reject::<()>(AppError::WrongIntentVersion as u16).await;
+    }
+    if app_id != APP_ID_SUI {
+        reject::<()>(AppError::WrongAppId as u16).await;
+    }
+}
+
+pub struct TransactionDataV1Output {
+    pub known: KnownTxn,
+    pub uses_gas_coin: bool,
+    pub sender: SuiAddressRaw,
+    pub gas: GasInfo,
+    pub expiration: Expiration,
+}
+
+const fn transaction_data_v1_parser<BS: Clone + Readable>(
 ) -> impl AsyncParser<TransactionDataV1, BS, Output = TransactionDataV1Output> {
     Action(
-        (
-            TransactionKind,
-            DefaultInterp,
-            gas_data_parser(),
-            DefaultInterp,
-        ),
-        |(v, _, gas_budget, _)| Some((v, gas_budget)),
+        (TransactionKind, DefaultInterp, DefaultInterp, DefaultInterp),
+        |(kind, sender, gas, expiration): (ProgrammableTransactionOutput, _, _, _)| {
+            Some(TransactionDataV1Output {
+                known: kind.known,
+                uses_gas_coin: kind.uses_gas_coin,
+                sender,
+                gas,
+                expiration,
+            })
+        },
     )
 }
 
-impl HasOutput<TransactionData> for TransactionData {
-    type Output = TransactionDataV1Output;
+impl HasOutput<TransactionData> for TransactionData {
+    type Output = TransactionDataV1Output;
+}
+
+impl<BS: Clone + Readable> AsyncParser<TransactionData, BS> for TransactionData {
+    type State<'c>
+        = impl Future<Output = Self::Output> + 'c
+    where
+        BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            let enum_variant =
+                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+            match enum_variant {
+                0 => {
+                    trace!("TransactionData: V1");
+                    transaction_data_v1_parser().parse(input).await
+                }
+                _ => {
+                    reject_on(
+                        core::file!(),
+                        core::line!(),
+                        SyscallError::NotSupported as u16,
+                    )
+                    .await
+                }
+            }
+        }
+    }
+}
+
+const fn tx_parser<BS: Clone + Readable>(
+) -> impl AsyncParser<IntentMessage, BS, Output = <TransactionData as HasOutput<TransactionData>>::Output>
+{
+    Action((intent_parser(), TransactionData), |(_, d)| Some(d))
 }
 
-impl<BS: Clone + Readable> AsyncParser<TransactionData, BS> for TransactionData {
+pub const SUMMARY_COMMANDS_LENGTH: usize = 4;
+
+/// The kind of a command, with the target of Move calls
+pub enum CommandSummary {
+    MoveCall {
+        package: SuiAddressRaw,
+        module: IdentifierString,
+        function: IdentifierString,
+    },
+    TransferObjects,
+    SplitCoins,
+    MergeCoins,
+    Publish,
+    MakeMoveVec,
+    Upgrade,
+}
+
+impl core::fmt::Display for CommandSummary {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CommandSummary::MoveCall {
+                package,
+                module,
+                function,
+            } => write!(
+                f,
+                "MoveCall {}::{}::{}",
+                ShortAddress(package),
+                module.as_str(),
+                function.as_str()
+            ),
+            CommandSummary::TransferObjects => write!(f, "TransferObjects"),
+            CommandSummary::SplitCoins => write!(f, "SplitCoins"),
+            CommandSummary::MergeCoins => write!(f, "MergeCoins"),
+            CommandSummary::Publish => write!(f, "Publish"),
+            CommandSummary::MakeMoveVec => write!(f, "MakeMoveVec"),
+            CommandSummary::Upgrade => write!(f, "Upgrade"),
+        }
+    }
+}
+
+/// Best-effort summary of a transaction which can't be clear-signed, shown when blind signing
+pub struct TxSummary {
+    pub sender: SuiAddressRaw,
+    pub gas_budget: u64,
+    pub input_count: u32,
+    pub command_count: u32,
+    /// The first commands, see `command_count` for how many there are
+    pub commands: ArrayVec<CommandSummary, SUMMARY_COMMANDS_LENGTH>,
+    pub expiration: Expiration,
+}
+
+async fn skip_bytes<BS: Readable>(input: &mut BS, length: usize) {
+    const CHUNK_SIZE: usize = 32;
+    for _ in 0..length / CHUNK_SIZE {
+        let _: [u8; CHUNK_SIZE] = input.read().await;
+    }
+    for _ in 0..length % CHUNK_SIZE {
+        let _: [u8; 1] = input.read().await;
+    }
+}
+
+async fn skip_arguments<BS: Clone + Readable>(input: &mut BS) {
+    let count = <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+    for _ in 0..count {
+        <DefaultInterp as AsyncParser<ArgumentSchema, BS>>::parse(&DefaultInterp, input).await;
+    }
+}
+
+// Unlike TypeTagSchema, this has no limit on the nesting or length of the type, as nothing is
+// rendered.
+async fn skip_type_tag<BS: Clone + Readable>(input: &mut BS) {
+    let mut pending: usize = 1;
+    while pending > 0 {
+        pending -= 1;
+        let tag = <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+        match tag {
+            6 => pending += 1,
+            7 => {
+                skip_bytes(input, SUI_ADDRESS_LENGTH).await;
+                for _ in 0..2 {
+                    let length =
+                        <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input)
+                            .await;
+                    skip_bytes(input, length as usize).await;
+                }
+                let type_params =
+                    <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+                pending += type_params as usize;
+            }
+            _ if primitive_type_name(tag).is_some() => {}
+            _ => {
+                reject_on::<()>(
+                    core::file!(),
+                    core::line!(),
+                    SyscallError::NotSupported as u16,
+                )
+                .await
+            }
+        }
+    }
+}
+
+// Package modules and dependencies, as found in Publish and Upgrade commands
+async fn skip_modules_and_dependencies<BS: Readable>(input: &mut BS) {
+    let modules = <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+    for _ in 0..modules {
+        let length =
+            <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+        skip_bytes(input, length as usize).await;
+    }
+    let dependencies =
+        <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+    skip_bytes(input, dependencies as usize * SUI_ADDRESS_LENGTH).await;
+}
+
+async fn skip_call_arg<BS: Readable>(input: &mut BS) {
+    // ObjectID, SequenceNumber, and ObjectDigest with its length prefix
+    const OBJECT_REF_LENGTH: usize = SUI_ADDRESS_LENGTH + 8 + 33;
+    // ObjectID, initial SequenceNumber and mutable flag
+    const SHARED_OBJECT_LENGTH: usize = SUI_ADDRESS_LENGTH + 8 + 1;
+    let enum_variant =
+        <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+    let length = match enum_variant {
+        0 => {
+            <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await as usize
+        }
+        1 => {
+            let object_variant =
+                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+            match object_variant {
+                // ImmOrOwnedObject and Receiving
+                0 | 2 => OBJECT_REF_LENGTH,
+                1 => SHARED_OBJECT_LENGTH,
+                _ => {
+                    reject_on(
+                        core::file!(),
+                        core::line!(),
+                        SyscallError::NotSupported as u16,
+                    )
+                    .await
+                }
+            }
+        }
+        _ => {
+            reject_on(
+                core::file!(),
+                core::line!(),
+                SyscallError::NotSupported as u16,
+            )
+            .await
+        }
+    };
+    skip_bytes(input, length).await;
+}
+
+impl HasOutput<CommandSummarySchema> for DefaultInterp {
+    type Output = CommandSummary;
+}
+
+impl<BS: Clone + Readable> AsyncParser<CommandSummarySchema, BS> for DefaultInterp {
     type State<'c>
         = impl Future<Output = Self::Output> + 'c
     where
@@ -605,10 +2214,88 @@ impl<BS: Clone + Readable> AsyncParser<TransactionData, BS> for TransactionData
                 <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
             match enum_variant {
                 0 => {
-                    trace!("TransactionData: V1");
-                    transaction_data_v1_parser().parse(input).await
+                    let package =
+                        <DefaultInterp as AsyncParser<ObjectID, BS>>::parse(&DefaultInterp, input)
+                            .await;
+                    let module = <DefaultInterp as AsyncParser<IdentifierSchema, BS>>::parse(
+                        &DefaultInterp,
+                        input,
+                    )
+                    .await;
+                    let function = <DefaultInterp as AsyncParser<IdentifierSchema, BS>>::parse(
+                        &DefaultInterp,
+                        input,
+                    )
+                    .await;
+                    let type_arguments =
+                        <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input)
+                            .await;
+                    for _ in 0..type_arguments {
+                        skip_type_tag(input).await;
+                    }
+                    skip_arguments(input).await;
+                    CommandSummary::MoveCall {
+                        package,
+                        module,
+                        function,
+                    }
+                }
+                1 => {
+                    skip_arguments(input).await;
+                    <DefaultInterp as AsyncParser<ArgumentSchema, BS>>::parse(
+                        &DefaultInterp,
+                        input,
+                    )
+                    .await;
+                    CommandSummary::TransferObjects
+                }
+                2 | 3 => {
+                    <DefaultInterp as AsyncParser<ArgumentSchema, BS>>::parse(
+                        &DefaultInterp,
+                        input,
+                    )
+                    .await;
+                    skip_arguments(input).await;
+                    if enum_variant == 2 {
+                        CommandSummary::SplitCoins
+                    } else {
+                        CommandSummary::MergeCoins
+                    }
+                }
+                4 => {
+                    skip_modules_and_dependencies(input).await;
+                    CommandSummary::Publish
+                }
+                5 => {
+                    let [has_type]: [u8; 1] = input.read().await;
+                    match has_type {
+                        0 => {}
+                        1 => skip_type_tag(input).await,
+                        _ => {
+                            reject_on::<()>(
+                                core::file!(),
+                                core::line!(),
+                                SyscallError::InvalidParameter as u16,
+                            )
+                            .await
+                        }
+                    }
+                    skip_arguments(input).await;
+                    CommandSummary::MakeMoveVec
+                }
+                6 => {
+                    skip_modules_and_dependencies(input).await;
+                    // Package being upgraded, and the upgrade ticket
+                    skip_bytes(input, SUI_ADDRESS_LENGTH).await;
+                    <DefaultInterp as AsyncParser<ArgumentSchema, BS>>::parse(
+                        &DefaultInterp,
+                        input,
+                    )
+                    .await;
+                    CommandSummary::Upgrade
                 }
                 _ => {
+                    trace!("CommandSummarySchema: Unknown enum: {}", enum_variant);
                     reject_on(
                         core::file!(),
                         core::line!(),
@@ -621,21 +2308,128 @@ impl<BS: Clone + Readable> AsyncParser<TransactionData, BS> for TransactionData
     }
 }
 
-const fn tx_parser<BS: Clone + Readable>(
-) -> impl AsyncParser<IntentMessage, BS, Output = <TransactionData as HasOutput<TransactionData>>::Output>
-{
-    Action((intent_parser(), TransactionData), |(_, d)| Some(d))
+impl HasOutput<TransactionSummarySchema> for DefaultInterp {
+    type Output = TxSummary;
 }
 
-pub async fn sign_apdu(io: HostIO, settings: Settings, ui: UserInterface) {
-    let mut input = match io.get_params::<2>() {
-        Some(v) => v,
+// Parses a programmable transaction without interpreting it, so that transactions which
+// tx_parser rejects can still be summarized. Only the intent must have been checked beforehand.
+impl<BS: Clone + Readable> AsyncParser<TransactionSummarySchema, BS> for DefaultInterp {
+    type State<'c>
+        = impl Future<Output = Self::Output> + 'c
+    where
+        BS: 'c;
+    fn parse<'a: 'c, 'b: 'c, 'c>(&'b self, input: &'a mut BS) -> Self::State<'c> {
+        async move {
+            intent_parser().parse(input).await;
+            let data_variant =
+                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+            let kind_variant =
+                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+            // TransactionData V1 of a ProgrammableTransaction
+            if data_variant != 0 || kind_variant != 0 {
+                reject_on::<()>(
+                    core::file!(),
+                    core::line!(),
+                    SyscallError::NotSupported as u16,
+                )
+                .await;
+            }
+            let input_count =
+                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+            for _ in 0..input_count {
+                skip_call_arg(input).await;
+            }
+            let command_count =
+                <DefaultInterp as AsyncParser<ULEB128, BS>>::parse(&DefaultInterp, input).await;
+            let mut commands = ArrayVec::new();
+            for _ in 0..command_count {
+                let command = <DefaultInterp as AsyncParser<CommandSummarySchema, BS>>::parse(
+                    &DefaultInterp,
+                    input,
+                )
+                .await;
+                let _ = commands.try_push(command);
+            }
+            let sender =
+                <DefaultInterp as AsyncParser<SuiAddress, BS>>::parse(&DefaultInterp, input).await;
+            let gas =
+                <DefaultInterp as AsyncParser<GasData, BS>>::parse(&DefaultInterp, input).await;
+            let expiration = <DefaultInterp as AsyncParser<TransactionExpiration, BS>>::parse(
+                &DefaultInterp,
+                input,
+            )
+            .await;
+            TxSummary {
+                sender,
+                gas_budget: gas.budget,
+                input_count,
+                command_count,
+                commands,
+                expiration,
+            }
+        }
+    }
+}
+
+/// The sign APDUs take an optional third parameter: a byte of `SIGN_FLAG_*` flags, of which
+/// only the `supported` ones may be set
+async fn get_sign_params(io: &HostIO, supported: u8) -> ([ByteStream; 2], u8) {
+    if let Some([txn, path, mut flags]) = io.get_params::<3>() {
+        let [flags]: [u8; 1] = flags.read().await;
+        if flags & !supported != 0 {
+            reject::<()>(SyscallError::InvalidParameter as u16).await;
+        }
+        return ([txn, path], flags);
+    }
+    match io.get_params::<2>() {
+        Some(v) => (v, 0),
         None => reject(SyscallError::InvalidParameter as u16).await,
-    };
+    }
+}
+
+/// Sign `digest`, and serialize the signature as `flags` request
+fn signature_response(
+    scheme: SignatureScheme,
+    path: &[u32],
+    digest: &[u8],
+    flags: u8,
+) -> Option<ArrayVec<u8, 98>> {
+    let (sig, public_key) = sign(scheme, path, digest)?;
+    let mut rv = ArrayVec::new();
+    if flags & SIGN_FLAG_SERIALIZED_SIGNATURE != 0 {
+        rv.try_push(scheme as u8).ok()?;
+        rv.try_extend_from_slice(&sig).ok()?;
+        rv.try_extend_from_slice(&public_key).ok()?;
+    } else {
+        rv.try_extend_from_slice(&sig).ok()?;
+    }
+    Some(rv)
+}
+
+/// Sign a transaction, as its sender or, with `as_sponsor`, as the owner of its gas coins
+pub async fn sign_apdu(io: HostIO, settings: Settings, ui: UserInterface, as_sponsor: bool) {
+    let (mut input, flags) = get_sign_params(
+        &io,
+        SIGN_FLAG_RETURN_DIGEST | SIGN_FLAG_SERIALIZED_SIGNATURE,
+    )
+    .await;
+    let descriptor = take_descriptor();
+    let multisig = take_multisig();
 
     // Read length, and move input[0] by one byte
     let length = usize::from_le_bytes(input[0].read().await);
 
+    // Other intents, like personal messages, must not be signed as transactions, even blindly
+    NoinlineFut(check_intent(
+        &mut input[0].clone(),
+        INTENT_SCOPE_TRANSACTION_DATA,
+    ))
+    .await;
+    if length < INTENT_LENGTH {
+        reject::<()>(SyscallError::InvalidParameter as u16).await;
+    }
+
     let known_txn = {
         let mut txn = input[0].clone();
         NoinlineFut(async move {
@@ -645,58 +2439,323 @@ pub async fn sign_apdu(io: HostIO, settings: Settings, ui: UserInterface) {
         .await
     };
 
+    // Whatever of an unrecognized transaction can be parsed is shown when blind signing it
+    let summary = if known_txn {
+        None
+    } else {
+        let mut txn = input[0].clone();
+        NoinlineFut(async move {
+            TryFuture(<DefaultInterp as AsyncParser<
+                TransactionSummarySchema,
+                ByteStream,
+            >>::parse(&DefaultInterp, &mut txn))
+            .await
+        })
+        .await
+    };
+
+    if !known_txn {
+        if as_sponsor || multisig.is_some() {
+            // Without parsing the transaction, we can't tell whether the gas coins only pay for
+            // gas, nor whether the multisig is its sender
+            reject::<()>(SyscallError::NotSupported as u16).await;
+        } else if !settings.get_blind_sign() {
+            ui.warn_tx_not_recognized();
+            reject::<()>(SyscallError::NotSupported as u16).await;
+        }
+    }
+
+    // The signature is over the hash of the whole intent message, while Sui identifies
+    // transactions by the digest of the TransactionData alone.
+    let (hash, digest) = {
+        let mut txn = input[0].clone();
+        NoinlineFut(async move {
+            let mut hasher: Blake2b = Hasher::new();
+            let mut digest_hasher: Blake2b = Hasher::new();
+            digest_hasher.update(b"TransactionData::");
+            // check_intent made sure each field of the intent is a single byte
+            let intent: [u8; INTENT_LENGTH] = txn.read().await;
+            hasher.update(&intent);
+            const CHUNK_SIZE: usize = 128;
+            let length = length - INTENT_LENGTH;
+            let (chunks, rem) = (length / CHUNK_SIZE, length % CHUNK_SIZE);
+            for _ in 0..chunks {
+                let b: [u8; CHUNK_SIZE] = txn.read().await;
+                hasher.update(&b);
+                digest_hasher.update(&b);
+            }
+            for _ in 0..rem {
+                let b: [u8; 1] = txn.read().await;
+                hasher.update(&b);
+                digest_hasher.update(&b);
+            }
+            let hash: HexHash<32> = hasher.finalize();
+            let digest: [u8; 32] = digest_hasher.finalize();
+            (hash, digest)
+        })
+        .await
+    };
+    let digest_str = base58_encode(&digest);
+
+    let path = BIP_PATH_PARSER.parse(&mut input[1].clone()).await;
+    let scheme = match path_scheme(&path) {
+        Some(scheme) => scheme,
+        None => reject(SyscallError::InvalidParameter as u16).await,
+    };
+
     if known_txn {
         let mut txn = input[0].clone();
-        let ((recipient, total_amount), gas_budget) = tx_parser().parse(&mut txn).await;
+        let TransactionDataV1Output {
+            known,
+            uses_gas_coin,
+            sender,
+            gas,
+            expiration,
+        } = tx_parser().parse(&mut txn).await;
 
-        let mut bs = input[1].clone();
-        let path = BIP_PATH_PARSER.parse(&mut bs).await;
-        if !path.starts_with(&BIP32_PREFIX[0..2]) {
-            reject::<()>(SyscallError::InvalidParameter as u16).await;
+        // A descriptor for the called function must match the arguments of the call
+        let descriptor = descriptor
+            .filter(|d| matches!(&known, KnownTxn::MoveCall { call, .. } if d.is_for(call)));
+        let described = match (&descriptor, &known) {
+            (Some(descriptor), KnownTxn::MoveCall { call, values, .. }) => {
+                match descriptor.describe(call, values) {
+                    Some(described) => Some(described),
+                    None => reject(AppError::DescriptorMismatch as u16).await,
+                }
+            }
+            _ => None,
+        };
+
+        // Members of a multisig sign for the multisig address
+        if let Some(multisig) = &multisig {
+            if with_public_key(scheme, &path, |address: &SuiPubKeyAddress| {
+                Some(multisig.has_member(scheme, address.public_key()))
+            }) != Some(true)
+            {
+                reject::<()>(AppError::NotMultisigMember as u16).await;
+            }
+        }
+        let signer = match &multisig {
+            Some(multisig) => Some(multisig.address),
+            None => with_public_key(scheme, &path, |address: &SuiPubKeyAddress| {
+                SuiAddressRaw::try_from(address.get_binary_address()).ok()
+            }),
+        };
+
+        if as_sponsor {
+            // Sender and sponsor sign the same message. So the sponsor must not be the sender,
+            // or the signature would authorize a transaction we haven't shown; and the gas coins
+            // must only pay for gas.
+            if signer != Some(gas.owner)
+                || sender == gas.owner
+                || uses_gas_coin
+                || gas.payment_count > gas.payment.len()
+            {
+                reject::<()>(SyscallError::InvalidParameter as u16).await;
+            }
+        } else if signer != Some(sender) {
+            // The review would show our address as "From" for someone else's transaction
+            reject::<()>(AppError::SenderMismatch as u16).await;
         }
 
         // Show prompts after all inputs have been parsed
-        if with_public_keys(&path, true, |_, address: &SuiPubKeyAddress| {
-            try_option(ui.confirm_sign_tx(address, recipient, total_amount, gas_budget))
+        check_path(&ui, scheme, &path).await;
+        let account = path_account(&path);
+        if as_sponsor {
+            if ui
+                .confirm_sponsor(sender, account, &gas, &expiration, &digest_str)
+                .is_none()
+            {
+                reject::<()>(StatusWords::UserCancelled as u16).await;
+            }
+        } else if with_public_key(scheme, &path, |key: &SuiPubKeyAddress| {
+            let address = &match &multisig {
+                Some(multisig) => Sender::Multisig(multisig),
+                None => Sender::Key(key),
+            };
+            let details = &TxDetails {
+                account,
+                gas_budget: gas.budget,
+                sponsor: (gas.owner != sender).then_some(gas.owner),
+                digest: digest_str,
+                expiration,
+            };
+            match &known {
+                KnownTxn::TransferCoin {
+                    recipient,
+                    coin,
+                    amount,
+                    merge,
+                } => ui.confirm_sign_tx(address, *recipient, coin, *amount, *merge, details),
+                KnownTxn::TransferObjects { recipient, objects } => {
+                    ui.confirm_transfer_objects(address, *recipient, objects, details)
+                }
+                KnownTxn::Payouts {
+                    coin,
+                    payouts,
+                    total_amount,
+                    merge,
+                } => ui.confirm_payouts(address, coin, payouts, *total_amount, *merge, details),
+                KnownTxn::MergeCoins(merge) => ui.confirm_merge_coins(address, merge, details),
+                KnownTxn::MoveCall {
+                    call,
+                    arguments,
+                    received,
+                    ..
+                } => match (&descriptor, &described) {
+                    (Some(descriptor), Some(described)) => ui.confirm_described_call(
+                        address, descriptor, call, described, received, details,
+                    ),
+                    _ => ui.confirm_move_call(address, call, arguments, received, details),
+                },
+                KnownTxn::Stake { validator, amount } => {
+                    ui.confirm_stake(address, *validator, *amount, details)
+                }
+                KnownTxn::Unstake { staked_sui } => {
+                    ui.confirm_unstake(address, *staked_sui, details)
+                }
+            }
         })
-        .ok()
         .is_none()
         {
             reject::<()>(StatusWords::UserCancelled as u16).await;
         };
-    } else if !settings.get_blind_sign() {
-        ui.warn_tx_not_recognized();
-        reject::<()>(SyscallError::NotSupported as u16).await;
+    } else {
+        // The summary shows its sender as "From", which must be our address as when
+        // clear-signing. Without a summary, the sender is unknown.
+        let summary = match summary {
+            Some(summary) => summary,
+            None => reject(SyscallError::NotSupported as u16).await,
+        };
+        let signer = with_public_key(scheme, &path, |address: &SuiPubKeyAddress| {
+            SuiAddressRaw::try_from(address.get_binary_address()).ok()
+        });
+        if signer != Some(summary.sender) {
+            reject::<()>(AppError::SenderMismatch as u16).await;
+        }
+
+        // Show prompts after all inputs have been parsed
+        check_path(&ui, scheme, &path).await;
+        if ui
+            .confirm_blind_sign_tx(&summary, path_account(&path), &hash, &digest_str)
+            .is_none()
+        {
+            reject::<()>(StatusWords::UserCancelled as u16).await;
+        };
     }
 
     NoinlineFut(async move {
+        if let Some(sig) = signature_response(scheme, &path, &hash.0, flags) {
+            let mut rv = ArrayVec::<u8, 130>::new();
+            let _ = rv.try_extend_from_slice(&sig);
+            if flags & SIGN_FLAG_RETURN_DIGEST != 0 {
+                let _ = rv.try_extend_from_slice(&digest);
+            }
+            io.result_final(&rv).await;
+        } else {
+            reject::<()>(SyscallError::Unspecified as u16).await;
+        }
+    })
+    .await
+}
+
+pub const PERSONAL_MESSAGE_DISPLAY_LENGTH: usize = 512;
+
+/// How a personal message is shown for review
+pub enum PersonalMessage<'a> {
+    Text(&'a str),
+    Hex(&'a [u8]),
+    /// Too long to be shown, only its hash can be
+    TooLong,
+}
+
+// The Nano fonts only have glyphs for printable ASCII, other text is shown as hex
+#[cfg(not(any(target_os = "stax", target_os = "flex")))]
+fn is_displayable_char(c: char) -> bool {
+    c == '\n' || c == ' ' || c.is_ascii_graphic()
+}
+
+#[cfg(any(target_os = "stax", target_os = "flex"))]
+fn is_displayable_char(c: char) -> bool {
+    c == '\n' || !c.is_control()
+}
+
+pub async fn sign_personal_message_apdu(io: HostIO, settings: Settings, ui: UserInterface) {
+    let (mut input, flags) = get_sign_params(&io, SIGN_FLAG_SERIALIZED_SIGNATURE).await;
+    // What was provided for the next transaction doesn't apply to messages, nor to transactions
+    // signed after them
+    let _ = take_descriptor();
+    let _ = take_multisig();
+
+    let length = usize::from_le_bytes(input[0].read().await);
+
+    let path = BIP_PATH_PARSER.parse(&mut input[1].clone()).await;
+    let scheme = match path_scheme(&path) {
+        Some(scheme) => scheme,
+        None => reject(SyscallError::InvalidParameter as u16).await,
+    };
+
+    NoinlineFut(async move {
+        // The signed message is the intent followed by the message as a BCS vector<u8>, so it
+        // can never be mistaken for a transaction
         let mut hasher: Blake2b = Hasher::new();
+        hasher.update(&[
+            INTENT_SCOPE_PERSONAL_MESSAGE as u8,
+            INTENT_VERSION_V0 as u8,
+            APP_ID_SUI as u8,
+        ]);
+        hasher.update(&uleb128_encode(length));
+
+        let displayable = length <= PERSONAL_MESSAGE_DISPLAY_LENGTH;
+        let mut message: ArrayVec<u8, PERSONAL_MESSAGE_DISPLAY_LENGTH> = ArrayVec::new();
         {
-            let mut txn = input[0].clone();
+            let mut msg = input[0].clone();
             const CHUNK_SIZE: usize = 128;
             let (chunks, rem) = (length / CHUNK_SIZE, length % CHUNK_SIZE);
             for _ in 0..chunks {
-                let b: [u8; CHUNK_SIZE] = txn.read().await;
+                let b: [u8; CHUNK_SIZE] = msg.read().await;
                 hasher.update(&b);
+                if displayable {
+                    let _ = message.try_extend_from_slice(&b);
+                }
             }
             for _ in 0..rem {
-                let b: [u8; 1] = txn.read().await;
+                let b: [u8; 1] = msg.read().await;
                 hasher.update(&b);
+                if displayable {
+                    let _ = message.try_extend_from_slice(&b);
+                }
             }
         }
         let hash: HexHash<32> = hasher.finalize();
-        if !known_txn {
-            // Show prompts after all inputs have been parsed
-            if ui.confirm_blind_sign_tx(&hash).is_none() {
-                reject::<()>(StatusWords::UserCancelled as u16).await;
-            };
-        }
-        let path = BIP_PATH_PARSER.parse(&mut input[1].clone()).await;
-        if !path.starts_with(&BIP32_PREFIX[0..2]) {
-            reject::<()>(SyscallError::InvalidParameter as u16).await;
+
+        let text = core::str::from_utf8(&message)
+            .ok()
+            .filter(|t| t.chars().all(is_displayable_char));
+        let shown = match text {
+            Some(text) if displayable => PersonalMessage::Text(text),
+            _ if displayable => PersonalMessage::Hex(&message),
+            _ => {
+                // Signing a message which can't be reviewed is blind signing
+                if !settings.get_blind_sign() {
+                    reject::<()>(SyscallError::NotSupported as u16).await;
+                }
+                PersonalMessage::TooLong
+            }
+        };
+
+        // Show prompts after all inputs have been parsed
+        check_path(&ui, scheme, &path).await;
+        if with_public_key(scheme, &path, |address: &SuiPubKeyAddress| {
+            ui.confirm_personal_message(address, path_account(&path), &shown, &hash)
+        })
+        .is_none()
+        {
+            reject::<()>(StatusWords::UserCancelled as u16).await;
         }
-        if let Some(sig) = { eddsa_sign(&path, true, &hash.0).ok() } {
-            io.result_final(&sig.0[0..]).await;
+
+        if let Some(sig) = signature_response(scheme, &path, &hash.0, flags) {
+            io.result_final(&sig).await;
         } else {
             reject::<()>(SyscallError::Unspecified as u16).await;
         }
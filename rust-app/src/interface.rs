@@ -29,16 +29,22 @@ pub struct ProgrammableTransaction;
 pub struct CommandSchema;
 pub struct ArgumentSchema;
 pub struct CallArgSchema;
+pub struct ProgrammableMoveCallSchema;
+pub struct IdentifierSchema;
+pub struct TypeTagSchema;
 
-pub type GasData = (
-    Vec<ObjectRef, { usize::MAX }>, // payment
-    SuiAddress,                     // owner
-    Amount,                         // price
-    Amount,                         // budget
-);
+// Tolerant parsers of a TransactionData and its commands, used to summarize transactions which
+// can't be clear-signed
+pub struct TransactionSummarySchema;
+pub struct CommandSummarySchema;
+
+// payment: Vec<ObjectRef>, owner: SuiAddress, price: Amount, budget: Amount
+pub struct GasData;
 
 pub struct TransactionExpiration;
 pub type EpochId = U64<{ Endianness::Little }>;
+// A checkpoint digest, which follows its length prefix
+pub const CHAIN_IDENTIFIER_LENGTH: usize = 32;
 
 pub type ObjectRef = (ObjectID, SequenceNumber, ObjectDigest);
 
@@ -64,20 +70,41 @@ pub type Amount = U64LE;
 
 pub type U64LE = U64<{ Endianness::Little }>;
 pub type U16LE = U16<{ Endianness::Little }>;
+pub type U32LE = U32<{ Endianness::Little }>;
 
-pub type Intent = (IntentVersion, IntentScope, AppId);
-pub type IntentVersion = ULEB128;
+pub type Intent = (IntentScope, IntentVersion, AppId);
 pub type IntentScope = ULEB128;
+pub type IntentVersion = ULEB128;
 pub type AppId = ULEB128;
 
+/// Length of an intent whose fields are all below 128, so single byte ULEB128s
+pub const INTENT_LENGTH: usize = 3;
+pub const INTENT_SCOPE_TRANSACTION_DATA: u32 = 0;
+pub const INTENT_SCOPE_PERSONAL_MESSAGE: u32 = 3;
+pub const INTENT_VERSION_V0: u32 = 0;
+pub const APP_ID_SUI: u32 = 0;
+
 // TODO: confirm if 33 is indeed ok for all uses of SHA3_256_HASH
 #[allow(non_camel_case_types)]
 pub type SHA3_256_HASH = Array<Byte, 33>;
 
 pub type SuiAddressRaw = [u8; SUI_ADDRESS_LENGTH];
 
-#[allow(dead_code)]
-pub struct SuiPubKeyAddress(ledger_device_sdk::ecc::ECPublicKey<65, 'E'>, SuiAddressRaw);
+/// The signature schemes of Sui, by their flag, which prefixes public keys to derive addresses
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SignatureScheme {
+    Ed25519 = 0x00,
+    Secp256k1 = 0x01,
+    Secp256r1 = 0x02,
+}
+
+/// The public key of a signature scheme, and its address
+pub struct SuiPubKeyAddress {
+    /// 32 bytes for ed25519, compressed 33 bytes for ECDSA
+    public_key: ArrayVec<u8, 33>,
+    address: SuiAddressRaw,
+}
 
 use arrayvec::ArrayVec;
 use ledger_crypto_helpers::common::{Address, HexSlice};
@@ -85,30 +112,71 @@ use ledger_crypto_helpers::eddsa::ed25519_public_key_bytes;
 use ledger_crypto_helpers::hasher::{Blake2b, Hasher};
 use ledger_device_sdk::io::SyscallError;
 
+impl SuiPubKeyAddress {
+    pub fn new(scheme: SignatureScheme, public_key: &[u8]) -> SuiPubKeyAddress {
+        let mut key = ArrayVec::new();
+        let _ = key.try_extend_from_slice(public_key);
+        let mut hasher: Blake2b = Hasher::new();
+        hasher.update(&[scheme as u8]);
+        hasher.update(&key);
+        SuiPubKeyAddress {
+            public_key: key,
+            address: hasher.finalize(),
+        }
+    }
+
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+}
+
 impl Address<SuiPubKeyAddress, ledger_device_sdk::ecc::ECPublicKey<65, 'E'>> for SuiPubKeyAddress {
     fn get_address(
         key: &ledger_device_sdk::ecc::ECPublicKey<65, 'E'>,
     ) -> Result<Self, SyscallError> {
-        let key_bytes = ed25519_public_key_bytes(key);
-        let mut tmp = ArrayVec::<u8, 33>::new();
-        let _ = tmp.try_push(0); // SIGNATURE_SCHEME_TO_FLAG['ED25519']
-        let _ = tmp.try_extend_from_slice(key_bytes);
-        let mut hasher: Blake2b = Hasher::new();
-        hasher.update(&tmp);
-        let hash: [u8; SUI_ADDRESS_LENGTH] = hasher.finalize();
-        Ok(SuiPubKeyAddress(key.clone(), hash))
+        Ok(SuiPubKeyAddress::new(
+            SignatureScheme::Ed25519,
+            ed25519_public_key_bytes(key),
+        ))
     }
     fn get_binary_address(&self) -> &[u8] {
-        &self.1
+        &self.address
     }
 }
 
 impl core::fmt::Display for SuiPubKeyAddress {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        write!(f, "0x{}", HexSlice(&self.1))
+        write!(f, "0x{}", HexSlice(&self.address))
     }
 }
 
+/// Flag of the optional sign parameter, to return the transaction digest after the signature
+pub const SIGN_FLAG_RETURN_DIGEST: u8 = 0x01;
+/// Flag of the optional sign parameter, to return the signature as Sui serializes it for RPCs:
+/// the flag of the signature scheme, the signature and the public key
+pub const SIGN_FLAG_SERIALIZED_SIGNATURE: u8 = 0x02;
+
+/// Status words for errors specific to this app
+#[repr(u16)]
+#[derive(Debug, Clone, Copy)]
+pub enum AppError {
+    /// The transaction sender is not the address of the signing key
+    SenderMismatch = 0xB009,
+    /// The intent is not for the kind of data the instruction signs
+    WrongIntentScope = 0xB00A,
+    WrongIntentVersion = 0xB00B,
+    /// The intent is for another application than Sui, e.g. Narwhal
+    WrongAppId = 0xB00C,
+    /// The descriptor is not signed by a trusted key
+    WrongDescriptorSignature = 0xB00D,
+    /// The arguments of a described call don't have the formats of the descriptor
+    DescriptorMismatch = 0xB00E,
+    /// The certificate is not of a key signing descriptors, or not signed by the Ledger PKI
+    WrongCertificate = 0xB00F,
+    /// The signing key is not a member of the provided multisig
+    NotMultisigMember = 0xB010,
+}
+
 #[repr(u8)]
 #[derive(Debug, TryFromPrimitive)]
 pub enum Ins {
@@ -116,6 +184,11 @@ pub enum Ins {
     VerifyAddress = 1,
     GetPubkey = 2,
     Sign = 3,
+    SignAsSponsor = 4,
+    SignPersonalMessage = 5,
+    ProvideDescriptor = 6,
+    ProvideCertificate = 7,
+    ProvideMultisig = 8,
     GetVersionStr = 0xfe,
     Exit = 0xff,
 }
@@ -0,0 +1,195 @@
+use crate::interface::*;
+use arrayvec::ArrayVec;
+use ledger_crypto_helpers::eddsa::ed25519_public_key_bytes;
+use ledger_crypto_helpers::hasher::{Hasher, SHA256};
+use ledger_device_sdk::ecc::{ECPrivateKey, Ed25519, Secp256k1, Secp256r1, SeedDerive};
+
+use core::convert::TryFrom;
+
+const HARDENED: u32 = 0x8000_0000;
+pub const SUI_COIN_TYPE: u32 = 784 | HARDENED;
+pub const ED25519_PURPOSE: u32 = 44 | HARDENED;
+pub const SECP256K1_PURPOSE: u32 = 54 | HARDENED;
+pub const SECP256R1_PURPOSE: u32 = 74 | HARDENED;
+
+const SECP256K1_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+const SECP256R1_ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63, 0x25, 0x51,
+];
+
+/// The scheme of the keys of a derivation path, by its purpose, as Sui wallets derive them.
+/// ECDSA paths must also have Sui's shape for them, `m/purpose'/784'/account'/change/index`.
+pub fn path_scheme(path: &[u32]) -> Option<SignatureScheme> {
+    let ecdsa_path = |rest: &[u32]| match rest {
+        [account, change, index] => {
+            account & HARDENED != 0 && change & HARDENED == 0 && index & HARDENED == 0
+        }
+        _ => false,
+    };
+    match path {
+        [ED25519_PURPOSE, SUI_COIN_TYPE, ..] => Some(SignatureScheme::Ed25519),
+        [SECP256K1_PURPOSE, SUI_COIN_TYPE, rest @ ..] if ecdsa_path(rest) => {
+            Some(SignatureScheme::Secp256k1)
+        }
+        [SECP256R1_PURPOSE, SUI_COIN_TYPE, rest @ ..] if ecdsa_path(rest) => {
+            Some(SignatureScheme::Secp256r1)
+        }
+        _ => None,
+    }
+}
+
+/// Whether a path has Sui's standard form for its scheme. Ed25519 paths must be
+/// `m/44'/784'/account'/change'/index'`, all hardened, while [`path_scheme`] already only
+/// accepts ECDSA paths of the standard form.
+pub fn is_standard_path(scheme: SignatureScheme, path: &[u32]) -> bool {
+    match scheme {
+        SignatureScheme::Ed25519 => {
+            path.len() == 5 && path.iter().all(|component| component & HARDENED != 0)
+        }
+        SignatureScheme::Secp256k1 | SignatureScheme::Secp256r1 => true,
+    }
+}
+
+/// The account index of a path, as wallets number accounts
+pub fn path_account(path: &[u32]) -> Option<u32> {
+    path.get(2).map(|account| account & !HARDENED)
+}
+
+/// Shows a derivation path as `m/44'/784'/0'/0'/0'`
+pub struct PathDisplay<'a>(pub &'a [u32]);
+
+impl core::fmt::Display for PathDisplay<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "m")?;
+        for component in self.0 {
+            write!(f, "/{}", component & !HARDENED)?;
+            if component & HARDENED != 0 {
+                write!(f, "'")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn ecdsa_private_key(scheme: SignatureScheme, path: &[u32]) -> ECPrivateKey<32, 'W'> {
+    match scheme {
+        SignatureScheme::Secp256r1 => Secp256r1::derive_from_path(path),
+        _ => Secp256k1::derive_from_path(path),
+    }
+}
+
+/// The compressed public key, as Sui derives addresses from it
+fn ecdsa_public_key(private_key: &ECPrivateKey<32, 'W'>) -> Option<[u8; 33]> {
+    let key = private_key.public_key().ok()?;
+    let mut rv = [0; 33];
+    rv[0] = 0x02 | (key.pubkey[64] & 1);
+    rv[1..].copy_from_slice(&key.pubkey[1..33]);
+    Some(rv)
+}
+
+/// Call `f` with the public key and address of `path`
+pub fn with_public_key<V>(
+    scheme: SignatureScheme,
+    path: &[u32],
+    f: impl FnOnce(&SuiPubKeyAddress) -> Option<V>,
+) -> Option<V> {
+    match scheme {
+        SignatureScheme::Ed25519 => {
+            let key = Ed25519::derive_from_path_slip10(path).public_key().ok()?;
+            f(&SuiPubKeyAddress::new(
+                scheme,
+                ed25519_public_key_bytes(&key),
+            ))
+        }
+        SignatureScheme::Secp256k1 | SignatureScheme::Secp256r1 => f(&SuiPubKeyAddress::new(
+            scheme,
+            &ecdsa_public_key(&ecdsa_private_key(scheme, path))?,
+        )),
+    }
+}
+
+/// Sign the 32 bytes digest of an intent message, as Sui verifies signatures of `scheme`. Also
+/// returns the public key of the key which signed.
+pub fn sign(
+    scheme: SignatureScheme,
+    path: &[u32],
+    digest: &[u8],
+) -> Option<([u8; 64], ArrayVec<u8, 33>)> {
+    match scheme {
+        SignatureScheme::Ed25519 => {
+            // Derived once, so the public key returned is that of the key which signed
+            let private_key = Ed25519::derive_from_path_slip10(path);
+            let key = private_key.public_key().ok()?;
+            let public_key = ArrayVec::try_from(ed25519_public_key_bytes(&key)).ok()?;
+            let (sig, _) = private_key.sign(digest).ok()?;
+            Some((sig, public_key))
+        }
+        SignatureScheme::Secp256k1 | SignatureScheme::Secp256r1 => {
+            // ECDSA signatures are over the SHA-256 hash of the digest
+            let mut hasher: SHA256 = Hasher::new();
+            hasher.update(digest);
+            let hash: [u8; 32] = hasher.finalize();
+            let private_key = ecdsa_private_key(scheme, path);
+            let public_key = ArrayVec::from(ecdsa_public_key(&private_key)?);
+            let (der, length, _) = private_key.deterministic_sign(&hash).ok()?;
+            let mut sig = der_to_compact(der.get(..length as usize)?)?;
+            let order = match scheme {
+                SignatureScheme::Secp256r1 => &SECP256R1_ORDER,
+                _ => &SECP256K1_ORDER,
+            };
+            normalize_s(&mut sig[32..], order);
+            Some((sig, public_key))
+        }
+    }
+}
+
+/// The integers r and s of a DER encoded ECDSA signature, as 32 big endian bytes each
+fn der_to_compact(der: &[u8]) -> Option<[u8; 64]> {
+    let mut rest = match der {
+        [0x30, length, rest @ ..] if *length as usize == rest.len() => rest,
+        _ => return None,
+    };
+    let mut rv = [0; 64];
+    for half in rv.chunks_exact_mut(32) {
+        let (int, tail) = match rest {
+            [0x02, length, tail @ ..] if *length as usize <= tail.len() => {
+                tail.split_at(*length as usize)
+            }
+            _ => return None,
+        };
+        // Integers are signed, so they may have a leading zero byte
+        let start = int.iter().position(|b| *b != 0).unwrap_or(int.len());
+        let int = &int[start..];
+        if int.len() > 32 {
+            return None;
+        }
+        half[32 - int.len()..].copy_from_slice(int);
+        rest = tail;
+    }
+    rest.is_empty().then_some(rv)
+}
+
+/// Replace s by `order - s` when s is above half the order, as Sui only accepts low s
+/// signatures
+fn normalize_s(s: &mut [u8], order: &[u8; 32]) {
+    let mut half_order = [0; 32];
+    let mut carry = 0;
+    for (h, o) in half_order.iter_mut().zip(order) {
+        *h = (o >> 1) | carry;
+        carry = (o & 1) << 7;
+    }
+    if *s <= half_order[..] {
+        return;
+    }
+    let mut borrow = 0;
+    for (b, o) in s.iter_mut().zip(order).rev() {
+        let difference = *o as i16 - *b as i16 - borrow;
+        *b = difference as u8;
+        borrow = (difference < 0) as i16;
+    }
+}
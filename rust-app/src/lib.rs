@@ -33,15 +33,28 @@ extern "C" fn sample_main() {
 
 pub mod interface;
 
+pub mod coins;
+
+pub mod pure;
+
 #[cfg(target_family = "bolos")]
 pub mod utils;
 
+#[cfg(target_family = "bolos")]
+pub mod keys;
+
 #[cfg(target_family = "bolos")]
 pub mod handle_apdu;
 
 #[cfg(target_family = "bolos")]
 pub mod implementation;
 
+#[cfg(target_family = "bolos")]
+pub mod descriptor;
+
+#[cfg(target_family = "bolos")]
+pub mod multisig;
+
 #[cfg(target_family = "bolos")]
 #[cfg(not(any(target_os = "stax", target_os = "flex")))]
 pub mod menu;
@@ -0,0 +1,156 @@
+use crate::handle_apdu::ProvidedForNextTx;
+use crate::interface::*;
+use alamgu_async_block::*;
+use arrayvec::{ArrayString, ArrayVec};
+use ledger_crypto_helpers::common::HexSlice;
+use ledger_crypto_helpers::hasher::{Blake2b, Hasher};
+use ledger_device_sdk::io::SyscallError;
+use ledger_log::trace;
+use ledger_parser_combinators::async_parser::*;
+
+use core::fmt::Write;
+
+/// Flag of multisig addresses, in place of a signature scheme
+pub const MULTISIG_FLAG: u8 = 0x03;
+/// Sui's maximum number of keys of a multisig
+pub const MULTISIG_MEMBERS_LENGTH: usize = 10;
+
+pub struct MultisigMember {
+    pub scheme: SignatureScheme,
+    pub public_key: ArrayVec<u8, 33>,
+    pub weight: u8,
+}
+
+/// A multisig public key, and its address
+pub struct Multisig {
+    pub members: ArrayVec<MultisigMember, MULTISIG_MEMBERS_LENGTH>,
+    pub threshold: u16,
+    pub address: SuiAddressRaw,
+}
+
+impl Multisig {
+    pub fn total_weight(&self) -> u16 {
+        self.members.iter().map(|m| m.weight as u16).sum()
+    }
+
+    pub fn has_member(&self, scheme: SignatureScheme, public_key: &[u8]) -> bool {
+        self.members
+            .iter()
+            .any(|m| m.scheme == scheme && m.public_key[..] == *public_key)
+    }
+}
+
+/// The address a transaction is signed for: the address of the signing key, or of a multisig
+/// which the key is a member of
+pub enum Sender<'a> {
+    Key(&'a SuiPubKeyAddress),
+    Multisig(&'a Multisig),
+}
+
+impl Sender<'_> {
+    /// Title of the address in reviews
+    pub fn title(&self) -> ArrayString<32> {
+        let mut rv = ArrayString::new();
+        let _ = match self {
+            Sender::Key(_) => write!(rv, "From"),
+            Sender::Multisig(multisig) => write!(
+                rv,
+                "From (multisig {}-of-{})",
+                multisig.threshold,
+                multisig.total_weight()
+            ),
+        };
+        rv
+    }
+}
+
+impl core::fmt::Display for Sender<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Sender::Key(address) => write!(f, "{address}"),
+            Sender::Multisig(multisig) => write!(f, "0x{}", HexSlice(&multisig.address)),
+        }
+    }
+}
+
+/// Read a multisig public key, rejecting it unless Sui would accept it
+async fn multisig_parser(input: &mut ByteStream) -> Multisig {
+    let threshold = u16::from_le_bytes(input.read().await);
+    let [count]: [u8; 1] = input.read().await;
+
+    // The address is over the flag, the threshold, then each key with its flag and its weight
+    let mut hasher: Blake2b = Hasher::new();
+    hasher.update(&[MULTISIG_FLAG]);
+    hasher.update(&threshold.to_le_bytes());
+
+    let mut members: ArrayVec<MultisigMember, MULTISIG_MEMBERS_LENGTH> = ArrayVec::new();
+    for _ in 0..count {
+        let [flag]: [u8; 1] = input.read().await;
+        let (scheme, length) = match flag {
+            0x00 => (SignatureScheme::Ed25519, 32),
+            0x01 => (SignatureScheme::Secp256k1, 33),
+            0x02 => (SignatureScheme::Secp256r1, 33),
+            _ => reject(SyscallError::InvalidParameter as u16).await,
+        };
+        let mut public_key = ArrayVec::new();
+        for _ in 0..length {
+            let [b]: [u8; 1] = input.read().await;
+            public_key.push(b);
+        }
+        let [weight]: [u8; 1] = input.read().await;
+
+        hasher.update(&[flag]);
+        hasher.update(&public_key);
+        hasher.update(&[weight]);
+
+        if weight == 0
+            || members
+                .iter()
+                .any(|m| m.scheme == scheme && m.public_key == public_key)
+        {
+            reject::<()>(SyscallError::InvalidParameter as u16).await;
+        }
+        let member = MultisigMember {
+            scheme,
+            public_key,
+            weight,
+        };
+        if members.try_push(member).is_err() {
+            reject::<()>(SyscallError::InvalidParameter as u16).await;
+        }
+    }
+
+    let multisig = Multisig {
+        members,
+        threshold,
+        address: hasher.finalize(),
+    };
+    if multisig.members.is_empty() || threshold == 0 || threshold > multisig.total_weight() {
+        reject::<()>(SyscallError::InvalidParameter as u16).await;
+    }
+    multisig
+}
+
+static PROVIDED_MULTISIG: ProvidedForNextTx<Multisig> = ProvidedForNextTx::new();
+
+/// The multisig provided since the last signed transaction or message, if any. It only applies
+/// to one transaction.
+pub fn take_multisig() -> Option<Multisig> {
+    PROVIDED_MULTISIG.take()
+}
+
+/// Keep a multisig public key, to sign the next transaction as one of its members, and return
+/// its address
+pub async fn provide_multisig_apdu(io: HostIO) {
+    let mut input = match io.get_params::<1>() {
+        Some(v) => v,
+        None => reject(SyscallError::InvalidParameter as u16).await,
+    };
+
+    let multisig = NoinlineFut(multisig_parser(&mut input[0])).await;
+    trace!("Multisig of {} members", multisig.members.len());
+
+    let address = multisig.address;
+    PROVIDED_MULTISIG.provide(multisig);
+    io.result_final(&address).await;
+}
@@ -0,0 +1,185 @@
+use crate::interface::*;
+use arrayvec::{ArrayString, ArrayVec};
+use core::convert::{TryFrom, TryInto};
+
+/// Pure inputs longer than this are skipped, and can't be decoded
+pub const PURE_VALUE_LENGTH: usize = 128;
+
+pub type PureBytes = ArrayVec<u8, PURE_VALUE_LENGTH>;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum PureKind {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    /// `vector<u8>`
+    Bytes,
+    /// `0x1::string::String` or `0x1::ascii::String`
+    String,
+}
+
+/// The Move type of a pure value, as declared by the parameter which receives it
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct PureType {
+    pub kind: PureKind,
+    /// Whether the value is a `0x1::option::Option` of `kind`
+    pub optional: bool,
+}
+
+impl PureType {
+    pub const U64: PureType = PureType::new(PureKind::U64);
+    pub const ADDRESS: PureType = PureType::new(PureKind::Address);
+
+    pub const fn new(kind: PureKind) -> PureType {
+        PureType {
+            kind,
+            optional: false,
+        }
+    }
+}
+
+pub enum PureValue<'a> {
+    Bool(bool),
+    /// Any of u8 to u128
+    Unsigned(u128),
+    /// Little endian, as serialized
+    U256([u8; 32]),
+    Address(SuiAddressRaw),
+    Bytes(&'a [u8]),
+    String(&'a str),
+    /// An empty `Option`
+    None,
+}
+
+/// Decode the BCS bytes of a pure value as `ty`. Fails unless they are exactly one value of
+/// that type.
+pub fn decode_pure(bytes: &[u8], ty: PureType) -> Option<PureValue<'_>> {
+    let bytes = if ty.optional {
+        match bytes.split_first()? {
+            (0, []) => return Some(PureValue::None),
+            (1, rest) => rest,
+            _ => return None,
+        }
+    } else {
+        bytes
+    };
+    let unsigned = |length: usize| -> Option<PureValue<'static>> {
+        if bytes.len() != length {
+            return None;
+        }
+        let mut le = [0; 16];
+        le[..length].copy_from_slice(bytes);
+        Some(PureValue::Unsigned(u128::from_le_bytes(le)))
+    };
+    match ty.kind {
+        PureKind::Bool => match bytes {
+            [0] => Some(PureValue::Bool(false)),
+            [1] => Some(PureValue::Bool(true)),
+            _ => None,
+        },
+        PureKind::U8 => unsigned(1),
+        PureKind::U16 => unsigned(2),
+        PureKind::U32 => unsigned(4),
+        PureKind::U64 => unsigned(8),
+        PureKind::U128 => unsigned(16),
+        PureKind::U256 => Some(PureValue::U256(bytes.try_into().ok()?)),
+        PureKind::Address => Some(PureValue::Address(bytes.try_into().ok()?)),
+        PureKind::Bytes | PureKind::String => {
+            let (length, rest) = decode_uleb128(bytes)?;
+            if rest.len() != length {
+                return None;
+            }
+            if ty.kind == PureKind::Bytes {
+                Some(PureValue::Bytes(rest))
+            } else {
+                Some(PureValue::String(core::str::from_utf8(rest).ok()?))
+            }
+        }
+    }
+}
+
+pub fn decode_uleb128(bytes: &[u8]) -> Option<(usize, &[u8])> {
+    let mut value: usize = 0;
+    for (i, b) in bytes.iter().enumerate().take(4) {
+        value |= ((b & 0x7f) as usize) << (7 * i);
+        if b & 0x80 == 0 {
+            return Some((value, &bytes[i + 1..]));
+        }
+    }
+    None
+}
+
+impl PureValue<'_> {
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            PureValue::Unsigned(value) => u64::try_from(*value).ok(),
+            _ => None,
+        }
+    }
+
+    pub fn as_address(&self) -> Option<SuiAddressRaw> {
+        match self {
+            PureValue::Address(address) => Some(*address),
+            _ => None,
+        }
+    }
+}
+
+impl core::fmt::Display for PureValue<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            PureValue::Bool(value) => write!(f, "{value}"),
+            PureValue::Unsigned(value) => write!(f, "{value}"),
+            PureValue::U256(le) => write!(f, "{}", u256_to_decimal(le).as_str()),
+            PureValue::Address(address) => {
+                write!(f, "0x")?;
+                address.iter().try_for_each(|b| write!(f, "{b:02x}"))
+            }
+            PureValue::Bytes(bytes) => {
+                write!(f, "0x")?;
+                bytes.iter().try_for_each(|b| write!(f, "{b:02x}"))
+            }
+            PureValue::String(s) => {
+                // Escape control characters, so the string can't fake other fields of a review
+                s.chars().try_for_each(|c| {
+                    if c.is_control() {
+                        write!(f, "{}", c.escape_default())
+                    } else {
+                        write!(f, "{c}")
+                    }
+                })
+            }
+            PureValue::None => write!(f, "None"),
+        }
+    }
+}
+
+/// u256::MAX has 78 digits
+fn u256_to_decimal(le: &[u8; 32]) -> ArrayString<78> {
+    let mut be = *le;
+    be.reverse();
+    let mut digits: ArrayVec<u8, 78> = ArrayVec::new();
+    loop {
+        // Divide the big endian number by 10 in place
+        let mut remainder: u16 = 0;
+        for b in be.iter_mut() {
+            let current = (remainder << 8) | *b as u16;
+            *b = (current / 10) as u8;
+            remainder = current % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+        if be.iter().all(|b| *b == 0) {
+            break;
+        }
+    }
+    let mut rv = ArrayString::new();
+    for d in digits.iter().rev() {
+        rv.push(*d as char);
+    }
+    rv
+}
@@ -1,5 +1,13 @@
+use crate::descriptor::{DescribedValue, Descriptor};
+use crate::implementation::{
+    Coin, CoinAmount, CoinMerge, Expiration, GasInfo, MoveCall, MoveCallArgument, PersonalMessage,
+    TransferAmount, TxDetails, TxSummary,
+};
 use crate::interface::*;
+use crate::keys::PathDisplay;
+use crate::multisig::Sender;
 use crate::utils::*;
+use arrayvec::ArrayString;
 use core::fmt::Write;
 use ledger_crypto_helpers::common::HexSlice;
 use ledger_crypto_helpers::hasher::HexHash;
@@ -17,34 +25,329 @@ impl UserInterface {
 
     pub fn confirm_sign_tx(
         &self,
-        address: &SuiPubKeyAddress,
+        address: &Sender,
         recipient: [u8; 32],
+        coin: &Coin,
+        amount: TransferAmount,
+        merge: Option<CoinMerge>,
+        details: &TxDetails,
+    ) -> Option<()> {
+        scroller("Transfer", |w| Ok(write!(w, "{}", coin.symbol())?))?;
+        if let Coin::Unknown(type_tag) = coin {
+            scroller_paginated("Coin Type", |w| Ok(write!(w, "{}", type_tag.as_str())?))?;
+        }
+
+        scroller_paginated(&address.title(), |w| Ok(write!(w, "{address}")?))?;
+        scroller_paginated("To", |w| Ok(write!(w, "0x{}", HexSlice(&recipient))?))?;
+
+        let amount = CoinAmount(coin, amount);
+        scroller_paginated("Amount", |w| Ok(write!(w, "{amount}")?))?;
+
+        if let Some(merge) = merge {
+            scroller_paginated("Merge Coins", |w| Ok(write!(w, "{merge}")?))?;
+        }
+
+        confirm_tx_details(details)?;
+        final_accept_prompt(&["Sign Transaction?"])
+    }
+
+    pub fn confirm_payouts(
+        &self,
+        address: &Sender,
+        coin: &Coin,
+        payouts: &[([u8; 32], u64)],
         total_amount: u64,
-        gas_budget: u64,
+        merge: Option<CoinMerge>,
+        details: &TxDetails,
     ) -> Option<()> {
-        scroller("Transfer", |w| Ok(write!(w, "SUI")?))?;
+        scroller("Batch Transfer", |w| {
+            Ok(write!(w, "{} payouts in {}", payouts.len(), coin.symbol())?)
+        })?;
+        if let Coin::Unknown(type_tag) = coin {
+            scroller_paginated("Coin Type", |w| Ok(write!(w, "{}", type_tag.as_str())?))?;
+        }
 
-        scroller_paginated("From", |w| Ok(write!(w, "{address}")?))?;
+        scroller_paginated(&address.title(), |w| Ok(write!(w, "{address}")?))?;
+
+        for (i, (recipient, amount)) in payouts.iter().enumerate() {
+            let mut title: ArrayString<16> = ArrayString::new();
+            write!(title, "To ({}/{})", i + 1, payouts.len()).ok()?;
+            scroller_paginated(&title, |w| Ok(write!(w, "0x{}", HexSlice(recipient))?))?;
+
+            let amount = CoinAmount(coin, TransferAmount::Amount(*amount));
+            let mut title: ArrayString<16> = ArrayString::new();
+            write!(title, "Amount ({}/{})", i + 1, payouts.len()).ok()?;
+            scroller_paginated(&title, |w| Ok(write!(w, "{amount}")?))?;
+        }
+
+        let total_amount = CoinAmount(coin, TransferAmount::Amount(total_amount));
+        scroller_paginated("Total Amount", |w| Ok(write!(w, "{total_amount}")?))?;
+
+        if let Some(merge) = merge {
+            scroller_paginated("Merge Coins", |w| Ok(write!(w, "{merge}")?))?;
+        }
+
+        confirm_tx_details(details)?;
+        final_accept_prompt(&["Sign Transaction?"])
+    }
+
+    pub fn confirm_transfer_objects(
+        &self,
+        address: &Sender,
+        recipient: [u8; 32],
+        objects: &[[u8; 32]],
+        details: &TxDetails,
+    ) -> Option<()> {
+        scroller("Transfer", |w| Ok(write!(w, "Objects")?))?;
+
+        scroller_paginated(&address.title(), |w| Ok(write!(w, "{address}")?))?;
         scroller_paginated("To", |w| Ok(write!(w, "0x{}", HexSlice(&recipient))?))?;
 
-        let (quotient, remainder_str) = get_amount_in_decimals(total_amount);
+        for (i, object) in objects.iter().enumerate() {
+            let mut title: ArrayString<16> = ArrayString::new();
+            write!(title, "Object {}", i + 1).ok()?;
+            scroller_paginated(&title, |w| Ok(write!(w, "0x{}", HexSlice(object))?))?;
+        }
+
+        confirm_tx_details(details)?;
+        final_accept_prompt(&["Sign Transaction?"])
+    }
+
+    pub fn confirm_merge_coins(
+        &self,
+        address: &Sender,
+        merge: &CoinMerge,
+        details: &TxDetails,
+    ) -> Option<()> {
+        scroller("Consolidate", |w| Ok(write!(w, "Coins")?))?;
+
+        scroller_paginated(&address.title(), |w| Ok(write!(w, "{address}")?))?;
+        scroller_paginated("Merge Coins", |w| Ok(write!(w, "{merge}")?))?;
+
+        confirm_tx_details(details)?;
+        final_accept_prompt(&["Sign Transaction?"])
+    }
+
+    pub fn confirm_move_call(
+        &self,
+        address: &Sender,
+        call: &MoveCall,
+        arguments: &[MoveCallArgument],
+        received: &[SuiAddressRaw],
+        details: &TxDetails,
+    ) -> Option<()> {
+        scroller("Move Call", |w| {
+            Ok(write!(w, "Not interpreted, review carefully")?)
+        })?;
+
+        scroller_paginated(&address.title(), |w| Ok(write!(w, "{address}")?))?;
+        scroller_paginated("Target", |w| {
+            Ok(write!(
+                w,
+                "0x{}::{}::{}",
+                HexSlice(&call.package),
+                call.module.as_str(),
+                call.function.as_str()
+            )?)
+        })?;
+
+        for (i, type_argument) in call.type_arguments.iter().enumerate() {
+            let mut title: ArrayString<16> = ArrayString::new();
+            write!(title, "Type Arg {}", i + 1).ok()?;
+            scroller_paginated(&title, |w| Ok(write!(w, "{}", type_argument.as_str())?))?;
+        }
+
+        for (i, argument) in arguments.iter().enumerate() {
+            let mut title: ArrayString<16> = ArrayString::new();
+            write!(title, "Argument {}", i + 1).ok()?;
+            scroller_paginated(&title, |w| Ok(write!(w, "{argument}")?))?;
+        }
+
+        for (i, object) in received.iter().enumerate() {
+            let mut title: ArrayString<16> = ArrayString::new();
+            write!(title, "Received Obj {}", i + 1).ok()?;
+            scroller_paginated(&title, |w| Ok(write!(w, "0x{}", HexSlice(object))?))?;
+        }
+
+        confirm_tx_details(details)?;
+        final_accept_prompt(&["Sign Transaction?"])
+    }
+
+    pub fn confirm_described_call(
+        &self,
+        address: &Sender,
+        descriptor: &Descriptor,
+        call: &MoveCall,
+        described: &[DescribedValue],
+        received: &[SuiAddressRaw],
+        details: &TxDetails,
+    ) -> Option<()> {
+        scroller("Move Call", |w| {
+            Ok(write!(w, "{}", descriptor.name.as_str())?)
+        })?;
+
+        scroller_paginated(&address.title(), |w| Ok(write!(w, "{address}")?))?;
+        scroller_paginated("Target", |w| {
+            Ok(write!(
+                w,
+                "0x{}::{}::{}",
+                HexSlice(&call.package),
+                call.module.as_str(),
+                call.function.as_str()
+            )?)
+        })?;
+
+        for (i, type_argument) in call.type_arguments.iter().enumerate() {
+            let mut title: ArrayString<16> = ArrayString::new();
+            write!(title, "Type Arg {}", i + 1).ok()?;
+            scroller_paginated(&title, |w| Ok(write!(w, "{}", type_argument.as_str())?))?;
+        }
+
+        for (argument, value) in descriptor.arguments.iter().zip(described) {
+            scroller_paginated(&argument.label, |w| Ok(write!(w, "{value}")?))?;
+        }
+
+        for (i, object) in received.iter().enumerate() {
+            let mut title: ArrayString<16> = ArrayString::new();
+            write!(title, "Received Obj {}", i + 1).ok()?;
+            scroller_paginated(&title, |w| Ok(write!(w, "0x{}", HexSlice(object))?))?;
+        }
+
+        confirm_tx_details(details)?;
+        final_accept_prompt(&["Sign Transaction?"])
+    }
+
+    pub fn confirm_stake(
+        &self,
+        address: &Sender,
+        validator: [u8; 32],
+        amount: u64,
+        details: &TxDetails,
+    ) -> Option<()> {
+        scroller("Stake", |w| Ok(write!(w, "SUI")?))?;
+
+        scroller_paginated(&address.title(), |w| Ok(write!(w, "{address}")?))?;
+        scroller_paginated("Validator", |w| {
+            Ok(write!(w, "0x{}", HexSlice(&validator))?)
+        })?;
+
+        let (quotient, remainder_str) = get_amount_in_decimals(amount);
         scroller_paginated("Amount", |w| {
-            Ok(write!(w, "SUI {quotient}.{}", remainder_str.as_str())?)
+            Ok(write!(w, "SUI {}.{}", quotient, remainder_str.as_str())?)
+        })?;
+
+        confirm_tx_details(details)?;
+        final_accept_prompt(&["Sign Transaction?"])
+    }
+
+    pub fn confirm_unstake(
+        &self,
+        address: &Sender,
+        staked_sui: [u8; 32],
+        details: &TxDetails,
+    ) -> Option<()> {
+        scroller("Unstake", |w| Ok(write!(w, "SUI")?))?;
+
+        scroller_paginated(&address.title(), |w| Ok(write!(w, "{address}")?))?;
+        scroller_paginated("Staked SUI", |w| {
+            Ok(write!(w, "0x{}", HexSlice(&staked_sui))?)
+        })?;
+
+        confirm_tx_details(details)?;
+        final_accept_prompt(&["Sign Transaction?"])
+    }
+
+    pub fn confirm_sponsor(
+        &self,
+        sender: [u8; 32],
+        account: Option<u32>,
+        gas: &GasInfo,
+        expiration: &Expiration,
+        digest: &ArrayString<44>,
+    ) -> Option<()> {
+        scroller_paginated("Sponsor Gas", |w| {
+            Ok(write!(w, "for transaction from 0x{}", HexSlice(&sender))?)
         })?;
+        confirm_account(account)?;
 
-        let (quotient, remainder_str) = get_amount_in_decimals(gas_budget);
+        let (quotient, remainder_str) = get_amount_in_decimals(gas.budget);
         scroller("Max Gas", |w| {
             Ok(write!(w, "SUI {}.{}", quotient, remainder_str.as_str())?)
         })?;
+
+        for (i, coin) in gas.payment.iter().enumerate() {
+            let mut title: ArrayString<16> = ArrayString::new();
+            write!(title, "Gas Coin {}", i + 1).ok()?;
+            scroller_paginated(&title, |w| Ok(write!(w, "0x{}", HexSlice(coin))?))?;
+        }
+
+        confirm_expiration(expiration)?;
+        scroller_paginated("Digest", |w| Ok(write!(w, "{}", digest.as_str())?))?;
         final_accept_prompt(&["Sign Transaction?"])
     }
 
-    pub fn confirm_blind_sign_tx(&self, hash: &HexHash<32>) -> Option<()> {
+    pub fn confirm_personal_message(
+        &self,
+        address: &SuiPubKeyAddress,
+        account: Option<u32>,
+        message: &PersonalMessage,
+        hash: &HexHash<32>,
+    ) -> Option<()> {
+        scroller("Sign", |w| Ok(write!(w, "Personal Message")?))?;
+
+        scroller_paginated("From", |w| Ok(write!(w, "{address}")?))?;
+        confirm_account(account)?;
+
+        match message {
+            PersonalMessage::Text(text) => {
+                scroller_paginated("Message", |w| Ok(write!(w, "{text}")?))?;
+            }
+            PersonalMessage::Hex(bytes) => {
+                scroller_paginated("Message (hex)", |w| Ok(write!(w, "{}", HexSlice(bytes))?))?;
+                scroller("Message Hash", |w| Ok(write!(w, "0x{hash}")?))?;
+            }
+            PersonalMessage::TooLong => {
+                scroller("WARNING", |w| Ok(write!(w, "Message too long to display")?))?;
+                scroller("Message Hash", |w| Ok(write!(w, "0x{hash}")?))?;
+            }
+        }
+        final_accept_prompt(&["Sign Message?"])
+    }
+
+    pub fn confirm_blind_sign_tx(
+        &self,
+        summary: &TxSummary,
+        account: Option<u32>,
+        hash: &HexHash<32>,
+        digest: &ArrayString<44>,
+    ) -> Option<()> {
         scroller("WARNING", |w| Ok(write!(w, "Transaction not recognized")?))?;
+        scroller_paginated("From", |w| {
+            Ok(write!(w, "0x{}", HexSlice(&summary.sender))?)
+        })?;
+        let (quotient, remainder_str) = get_amount_in_decimals(summary.gas_budget);
+        scroller("Max Gas", |w| {
+            Ok(write!(w, "SUI {}.{}", quotient, remainder_str.as_str())?)
+        })?;
+        scroller("Inputs", |w| Ok(write!(w, "{}", summary.input_count)?))?;
+        scroller("Commands", |w| Ok(write!(w, "{}", summary.command_count)?))?;
+        for (i, command) in summary.commands.iter().enumerate() {
+            let mut title: ArrayString<16> = ArrayString::new();
+            write!(title, "Command ({}/{})", i + 1, summary.command_count).ok()?;
+            scroller_paginated(&title, |w| Ok(write!(w, "{command}")?))?;
+        }
+        confirm_expiration(&summary.expiration)?;
+        confirm_account(account)?;
+        scroller_paginated("Digest", |w| Ok(write!(w, "{}", digest.as_str())?))?;
         scroller("Transaction Hash", |w| Ok(write!(w, "0x{hash}")?))?;
         final_accept_prompt(&["Blind Sign Transaction?"])
     }
 
+    pub fn warn_unusual_path(&self, path: &[u32]) -> Option<()> {
+        scroller("WARNING", |w| Ok(write!(w, "Unusual derivation path")?))?;
+        scroller_paginated("Path", |w| Ok(write!(w, "{}", PathDisplay(path))?))
+    }
+
     pub fn warn_tx_not_recognized(&self) {
         scroller("WARNING", |w| {
             Ok(write!(
@@ -55,6 +358,35 @@ impl UserInterface {
     }
 }
 
+fn confirm_tx_details(details: &TxDetails) -> Option<()> {
+    confirm_account(details.account)?;
+    if let Some(sponsor) = details.sponsor {
+        scroller_paginated("Gas Paid By", |w| {
+            Ok(write!(w, "0x{}", HexSlice(&sponsor))?)
+        })?;
+    }
+    let (quotient, remainder_str) = get_amount_in_decimals(details.gas_budget);
+    scroller("Max Gas", |w| {
+        Ok(write!(w, "SUI {}.{}", quotient, remainder_str.as_str())?)
+    })?;
+    confirm_expiration(&details.expiration)?;
+    scroller_paginated("Digest", |w| Ok(write!(w, "{}", details.digest.as_str())?))
+}
+
+fn confirm_account(account: Option<u32>) -> Option<()> {
+    match account {
+        Some(account) => scroller("Account", |w| Ok(write!(w, "{account}")?)),
+        None => Some(()),
+    }
+}
+
+fn confirm_expiration(expiration: &Expiration) -> Option<()> {
+    if expiration.is_none() {
+        return Some(());
+    }
+    scroller_paginated("Expiration", |w| Ok(write!(w, "{expiration}")?))
+}
+
 #[cfg(not(target_os = "nanos"))]
 #[inline(never)]
 pub fn scroller<F: for<'b> Fn(&mut PromptWrite<'b, 16>) -> Result<(), ScrollerError>>(
@@ -1,8 +1,20 @@
+use crate::descriptor::{DescribedValue, Descriptor};
+use crate::implementation::{
+    Coin, CoinAmount, CoinMerge, Expiration, GasInfo, MoveCall, MoveCallArgument, PersonalMessage,
+    TransferAmount, TxDetails, TxSummary,
+};
 use crate::interface::*;
+use crate::keys::PathDisplay;
+use crate::multisig::Sender;
 use crate::utils::*;
 
 extern crate alloc;
 use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use arrayvec::ArrayString;
 
 use core::cell::RefCell;
 use include_gif::include_gif;
@@ -44,41 +56,314 @@ impl UserInterface {
 
     pub fn confirm_sign_tx(
         &self,
-        address: &SuiPubKeyAddress,
+        address: &Sender,
         recipient: [u8; 32],
+        coin: &Coin,
+        amount: TransferAmount,
+        merge: Option<CoinMerge>,
+        details: &TxDetails,
+    ) -> Option<()> {
+        self.do_refresh.replace(true);
+        let mut fields: Vec<(String, String)> = vec![];
+        if let Coin::Unknown(type_tag) = coin {
+            fields.push((String::from("Coin Type"), String::from(type_tag.as_str())));
+        }
+        fields.push((String::from(address.title().as_str()), format!("{address}")));
+        fields.push((String::from("To"), format!("0x{}", HexSlice(&recipient))));
+        fields.push((
+            String::from("Amount"),
+            format!("{}", CoinAmount(coin, amount)),
+        ));
+        if let Some(merge) = merge {
+            fields.push((String::from("Merge Coins"), format!("{merge}")));
+        }
+        push_tx_details(&mut fields, details);
+        self.review(&format!("Transfer {}", coin.symbol()), "", &fields)
+    }
+
+    pub fn confirm_payouts(
+        &self,
+        address: &Sender,
+        coin: &Coin,
+        payouts: &[([u8; 32], u64)],
         total_amount: u64,
-        gas_budget: u64,
+        merge: Option<CoinMerge>,
+        details: &TxDetails,
+    ) -> Option<()> {
+        self.do_refresh.replace(true);
+        let mut fields: Vec<(String, String)> = vec![];
+        if let Coin::Unknown(type_tag) = coin {
+            fields.push((String::from("Coin Type"), String::from(type_tag.as_str())));
+        }
+        fields.push((String::from(address.title().as_str()), format!("{address}")));
+        for (i, (recipient, amount)) in payouts.iter().enumerate() {
+            fields.push((
+                format!("To ({}/{})", i + 1, payouts.len()),
+                format!("0x{}", HexSlice(recipient)),
+            ));
+            fields.push((
+                format!("Amount ({}/{})", i + 1, payouts.len()),
+                format!("{}", CoinAmount(coin, TransferAmount::Amount(*amount))),
+            ));
+        }
+        fields.push((
+            String::from("Total Amount"),
+            format!("{}", CoinAmount(coin, TransferAmount::Amount(total_amount))),
+        ));
+        if let Some(merge) = merge {
+            fields.push((String::from("Merge Coins"), format!("{merge}")));
+        }
+        push_tx_details(&mut fields, details);
+        self.review(&format!("Batch transfer {}", coin.symbol()), "", &fields)
+    }
+
+    pub fn confirm_transfer_objects(
+        &self,
+        address: &Sender,
+        recipient: [u8; 32],
+        objects: &[[u8; 32]],
+        details: &TxDetails,
     ) -> Option<()> {
         self.do_refresh.replace(true);
-        let tx_fields = [
-            Field {
-                name: "From",
-                value: &format!("{address}"),
-            },
-            Field {
-                name: "To",
-                value: &format!("0x{}", HexSlice(&recipient)),
-            },
-            Field {
-                name: "Amount",
-                value: {
-                    let (quotient, remainder_str) = get_amount_in_decimals(total_amount);
-                    &format!("SUI {}.{}", quotient, remainder_str.as_str())
-                },
-            },
-            Field {
-                name: "Max Gas",
-                value: {
-                    let (quotient, remainder_str) = get_amount_in_decimals(gas_budget);
-                    &format!("SUI {}.{}", quotient, remainder_str.as_str())
-                },
-            },
+        let mut fields: Vec<(String, String)> = vec![
+            (String::from(address.title().as_str()), format!("{address}")),
+            (String::from("To"), format!("0x{}", HexSlice(&recipient))),
         ];
+        for (i, object) in objects.iter().enumerate() {
+            fields.push((
+                format!("Object {}", i + 1),
+                format!("0x{}", HexSlice(object)),
+            ));
+        }
+        push_tx_details(&mut fields, details);
+        self.review("Transfer objects", "", &fields)
+    }
 
-        let success = NbglReview::new()
-            .glyph(&APP_ICON)
-            .titles("Transfer SUI", "", "Sign Transaction?")
+    pub fn confirm_merge_coins(
+        &self,
+        address: &Sender,
+        merge: &CoinMerge,
+        details: &TxDetails,
+    ) -> Option<()> {
+        self.do_refresh.replace(true);
+        let mut fields: Vec<(String, String)> = vec![
+            (String::from(address.title().as_str()), format!("{address}")),
+            (String::from("Merge Coins"), format!("{merge}")),
+        ];
+        push_tx_details(&mut fields, details);
+        self.review("Consolidate coins", "", &fields)
+    }
+
+    pub fn confirm_move_call(
+        &self,
+        address: &Sender,
+        call: &MoveCall,
+        arguments: &[MoveCallArgument],
+        received: &[SuiAddressRaw],
+        details: &TxDetails,
+    ) -> Option<()> {
+        self.do_refresh.replace(true);
+        let mut fields: Vec<(String, String)> = vec![
+            (String::from(address.title().as_str()), format!("{address}")),
+            (
+                String::from("Target"),
+                format!(
+                    "0x{}::{}::{}",
+                    HexSlice(&call.package),
+                    call.module.as_str(),
+                    call.function.as_str()
+                ),
+            ),
+        ];
+        for (i, type_argument) in call.type_arguments.iter().enumerate() {
+            fields.push((
+                format!("Type argument {}", i + 1),
+                String::from(type_argument.as_str()),
+            ));
+        }
+        for (i, argument) in arguments.iter().enumerate() {
+            fields.push((format!("Argument {}", i + 1), format!("{argument}")));
+        }
+        for (i, object) in received.iter().enumerate() {
+            fields.push((
+                format!("Received object {}", i + 1),
+                format!("0x{}", HexSlice(object)),
+            ));
+        }
+        push_tx_details(&mut fields, details);
+        self.review(
+            "Review Move call",
+            "Arguments are not interpreted, review carefully",
+            &fields,
+        )
+    }
+
+    pub fn confirm_described_call(
+        &self,
+        address: &Sender,
+        descriptor: &Descriptor,
+        call: &MoveCall,
+        described: &[DescribedValue],
+        received: &[SuiAddressRaw],
+        details: &TxDetails,
+    ) -> Option<()> {
+        self.do_refresh.replace(true);
+        let mut fields: Vec<(String, String)> = vec![
+            (String::from(address.title().as_str()), format!("{address}")),
+            (
+                String::from("Target"),
+                format!(
+                    "0x{}::{}::{}",
+                    HexSlice(&call.package),
+                    call.module.as_str(),
+                    call.function.as_str()
+                ),
+            ),
+        ];
+        for (i, type_argument) in call.type_arguments.iter().enumerate() {
+            fields.push((
+                format!("Type argument {}", i + 1),
+                String::from(type_argument.as_str()),
+            ));
+        }
+        for (argument, value) in descriptor.arguments.iter().zip(described) {
+            fields.push((String::from(argument.label.as_str()), format!("{value}")));
+        }
+        for (i, object) in received.iter().enumerate() {
+            fields.push((
+                format!("Received object {}", i + 1),
+                format!("0x{}", HexSlice(object)),
+            ));
+        }
+        push_tx_details(&mut fields, details);
+        self.review(descriptor.name.as_str(), "", &fields)
+    }
+
+    pub fn confirm_stake(
+        &self,
+        address: &Sender,
+        validator: [u8; 32],
+        amount: u64,
+        details: &TxDetails,
+    ) -> Option<()> {
+        self.do_refresh.replace(true);
+        let mut fields: Vec<(String, String)> = vec![
+            (String::from(address.title().as_str()), format!("{address}")),
+            (
+                String::from("Validator"),
+                format!("0x{}", HexSlice(&validator)),
+            ),
+            (String::from("Amount"), {
+                let (quotient, remainder_str) = get_amount_in_decimals(amount);
+                format!("SUI {}.{}", quotient, remainder_str.as_str())
+            }),
+        ];
+        push_tx_details(&mut fields, details);
+        self.review("Stake SUI", "", &fields)
+    }
+
+    pub fn confirm_unstake(
+        &self,
+        address: &Sender,
+        staked_sui: [u8; 32],
+        details: &TxDetails,
+    ) -> Option<()> {
+        self.do_refresh.replace(true);
+        let mut fields: Vec<(String, String)> = vec![
+            (String::from(address.title().as_str()), format!("{address}")),
+            (
+                String::from("Staked SUI"),
+                format!("0x{}", HexSlice(&staked_sui)),
+            ),
+        ];
+        push_tx_details(&mut fields, details);
+        self.review("Unstake SUI", "", &fields)
+    }
+
+    pub fn confirm_sponsor(
+        &self,
+        sender: [u8; 32],
+        account: Option<u32>,
+        gas: &GasInfo,
+        expiration: &Expiration,
+        digest: &ArrayString<44>,
+    ) -> Option<()> {
+        self.do_refresh.replace(true);
+        let mut fields: Vec<(String, String)> = vec![];
+        push_account(&mut fields, account);
+        fields.push((String::from("Max Gas"), {
+            let (quotient, remainder_str) = get_amount_in_decimals(gas.budget);
+            format!("SUI {}.{}", quotient, remainder_str.as_str())
+        }));
+        for (i, coin) in gas.payment.iter().enumerate() {
+            fields.push((
+                format!("Gas coin {}", i + 1),
+                format!("0x{}", HexSlice(coin)),
+            ));
+        }
+        push_expiration(&mut fields, expiration);
+        fields.push((String::from("Digest"), String::from(digest.as_str())));
+        self.review(
+            "Sponsor gas",
+            &format!("for transaction from 0x{}", HexSlice(&sender)),
+            &fields,
+        )
+    }
+
+    pub fn confirm_personal_message(
+        &self,
+        address: &SuiPubKeyAddress,
+        account: Option<u32>,
+        message: &PersonalMessage,
+        hash: &HexHash<32>,
+    ) -> Option<()> {
+        self.do_refresh.replace(true);
+        let from_str = format!("{address}");
+        let account_str = account.map(|account| format!("{account}"));
+        let hash_str = format!("0x{hash}");
+        let hex_str;
+        let mut tx_fields = vec![Field {
+            name: "From",
+            value: &from_str,
+        }];
+        if let Some(account_str) = &account_str {
+            tx_fields.push(Field {
+                name: "Account",
+                value: account_str,
+            });
+        }
+        match message {
+            PersonalMessage::Text(text) => tx_fields.push(Field {
+                name: "Message",
+                value: text,
+            }),
+            PersonalMessage::Hex(bytes) => {
+                hex_str = format!("{}", HexSlice(bytes));
+                tx_fields.push(Field {
+                    name: "Message (hex)",
+                    value: &hex_str,
+                });
+                tx_fields.push(Field {
+                    name: "Message hash",
+                    value: &hash_str,
+                });
+            }
+            PersonalMessage::TooLong => tx_fields.push(Field {
+                name: "Message hash",
+                value: &hash_str,
+            }),
+        }
+
+        let mut review = NbglReview::new().glyph(&APP_ICON);
+        if let PersonalMessage::TooLong = message {
+            review = review.blind();
+        }
+        let success = review
+            .titles("Review message", "", "Sign message?")
             .show(&tx_fields);
+        NbglReviewStatus::new()
+            .status_type(StatusType::Message)
+            .show(success);
         if success {
             Some(())
         } else {
@@ -86,12 +371,45 @@ impl UserInterface {
         }
     }
 
-    pub fn confirm_blind_sign_tx(&self, hash: &HexHash<32>) -> Option<()> {
+    pub fn confirm_blind_sign_tx(
+        &self,
+        summary: &TxSummary,
+        account: Option<u32>,
+        hash: &HexHash<32>,
+        digest: &ArrayString<44>,
+    ) -> Option<()> {
         self.do_refresh.replace(true);
-        let tx_fields = [Field {
-            name: "Transaction hash",
-            value: &format!("0x{hash}"),
-        }];
+        let mut fields: Vec<(String, String)> = vec![];
+        fields.push((
+            String::from("From"),
+            format!("0x{}", HexSlice(&summary.sender)),
+        ));
+        fields.push((String::from("Max Gas"), {
+            let (quotient, remainder_str) = get_amount_in_decimals(summary.gas_budget);
+            format!("SUI {}.{}", quotient, remainder_str.as_str())
+        }));
+        fields.push((String::from("Inputs"), format!("{}", summary.input_count)));
+        fields.push((
+            String::from("Commands"),
+            format!("{}", summary.command_count),
+        ));
+        for (i, command) in summary.commands.iter().enumerate() {
+            fields.push((
+                format!("Command ({}/{})", i + 1, summary.command_count),
+                format!("{command}"),
+            ));
+        }
+        push_expiration(&mut fields, &summary.expiration);
+        push_account(&mut fields, account);
+        fields.push((String::from("Digest"), String::from(digest.as_str())));
+        fields.push((String::from("Transaction hash"), format!("0x{hash}")));
+        let tx_fields: Vec<Field> = fields
+            .iter()
+            .map(|(name, value)| Field {
+                name: name.as_str(),
+                value: value.as_str(),
+            })
+            .collect();
 
         let success = NbglReview::new()
             .glyph(&APP_ICON)
@@ -108,6 +426,41 @@ impl UserInterface {
         }
     }
 
+    fn review(&self, title: &str, subtitle: &str, fields: &[(String, String)]) -> Option<()> {
+        let tx_fields: Vec<Field> = fields
+            .iter()
+            .map(|(name, value)| Field {
+                name: name.as_str(),
+                value: value.as_str(),
+            })
+            .collect();
+
+        let success = NbglReview::new()
+            .glyph(&APP_ICON)
+            .titles(title, subtitle, "Sign Transaction?")
+            .show(&tx_fields);
+        if success {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    pub fn warn_unusual_path(&self, path: &[u32]) -> Option<()> {
+        self.do_refresh.replace(true);
+        let choice = NbglChoice::new().show(
+            "Unusual derivation path",
+            &format!("{}", PathDisplay(path)),
+            "Continue",
+            "Reject",
+        );
+        if choice {
+            Some(())
+        } else {
+            None
+        }
+    }
+
     pub fn warn_tx_not_recognized(&self) {
         let choice = NbglChoice::new().show(
             "This transaction cannot be clear-signed",
@@ -125,3 +478,34 @@ impl UserInterface {
         }
     }
 }
+
+fn push_tx_details(fields: &mut Vec<(String, String)>, details: &TxDetails) {
+    push_account(fields, details.account);
+    if let Some(sponsor) = details.sponsor {
+        fields.push((
+            String::from("Gas paid by"),
+            format!("0x{}", HexSlice(&sponsor)),
+        ));
+    }
+    fields.push((String::from("Max Gas"), {
+        let (quotient, remainder_str) = get_amount_in_decimals(details.gas_budget);
+        format!("SUI {}.{}", quotient, remainder_str.as_str())
+    }));
+    push_expiration(fields, &details.expiration);
+    fields.push((
+        String::from("Digest"),
+        String::from(details.digest.as_str()),
+    ));
+}
+
+fn push_account(fields: &mut Vec<(String, String)>, account: Option<u32>) {
+    if let Some(account) = account {
+        fields.push((String::from("Account"), format!("{account}")));
+    }
+}
+
+fn push_expiration(fields: &mut Vec<(String, String)>, expiration: &Expiration) {
+    if !expiration.is_none() {
+        fields.push((String::from("Expiration"), format!("{expiration}")));
+    }
+}
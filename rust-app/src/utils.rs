@@ -26,14 +26,18 @@ impl<F: Future> Future for NoinlineFut<F> {
     }
 }
 
-use arrayvec::ArrayString;
+use arrayvec::{ArrayString, ArrayVec};
 
-pub fn get_amount_in_decimals(amount: u64) -> (u64, ArrayString<12>) {
-    let factor_pow = 9;
+pub fn get_amount_in_decimals(amount: u64) -> (u64, ArrayString<20>) {
+    get_amount_with_decimals(amount, 9)
+}
+
+pub fn get_amount_with_decimals(amount: u64, decimals: u32) -> (u64, ArrayString<20>) {
+    let factor_pow = decimals;
     let factor = u64::pow(10, factor_pow);
     let quotient = amount / factor;
     let remainder = amount % factor;
-    let mut remainder_str: ArrayString<12> = ArrayString::new();
+    let mut remainder_str: ArrayString<20> = ArrayString::new();
     {
         // Make a string for the remainder, containing at lease one zero
         // So 1 SUI will be displayed as "1.0"
@@ -47,6 +51,101 @@ pub fn get_amount_in_decimals(amount: u64) -> (u64, ArrayString<12>) {
                 break;
             }
         }
+        if remainder_str.is_empty() {
+            let _ = remainder_str.try_push('0');
+        }
     }
     (quotient, remainder_str)
 }
+
+/// Encodes a length the way BCS prefixes vectors
+pub fn uleb128_encode(mut value: usize) -> ArrayVec<u8, 10> {
+    let mut bytes = ArrayVec::new();
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            let _ = bytes.try_push(byte);
+            return bytes;
+        }
+        let _ = bytes.try_push(byte | 0x80);
+    }
+}
+
+/// Encodes a digest in base58, the way Sui shows transaction digests
+pub fn base58_encode(bytes: &[u8; 32]) -> ArrayString<44> {
+    const ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+    // Base 58 digits, least significant first
+    let mut digits = [0u8; 44];
+    let mut length = 0;
+    for byte in bytes {
+        let mut carry = *byte as u32;
+        for digit in digits[..length].iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits[length] = (carry % 58) as u8;
+            length += 1;
+            carry /= 58;
+        }
+    }
+    let mut encoded = ArrayString::new();
+    // Leading zero bytes are encoded as ones
+    for _ in bytes.iter().take_while(|b| **b == 0) {
+        let _ = encoded.try_push('1');
+    }
+    for digit in digits[..length].iter().rev() {
+        let _ = encoded.try_push(ALPHABET[*digit as usize] as char);
+    }
+    encoded
+}
+
+/// Displays an address without its leading zeros, the way Move prints e.g. "0x2::sui::SUI"
+pub struct ShortAddress<'a>(pub &'a [u8; 32]);
+
+impl core::fmt::Display for ShortAddress<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.0.iter().position(|b| *b != 0) {
+            None => write!(f, "0x0"),
+            Some(i) => {
+                write!(f, "0x{:x}", self.0[i])?;
+                for b in &self.0[i + 1..] {
+                    write!(f, "{:02x}", b)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Unix time in seconds, shown as a UTC date and time
+pub struct Timestamp(pub u64);
+
+impl core::fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (days, seconds) = (self.0 / 86400, self.0 % 86400);
+        // Civil date from days since 1970-01-01, c.f.
+        // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+        let z = days + 719468;
+        let era = z / 146097;
+        let doe = z % 146097;
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + u64::from(month <= 2);
+        write!(
+            f,
+            "{}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+            year,
+            month,
+            day,
+            seconds / 3600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    }
+}